@@ -77,16 +77,17 @@ mod test {
 
         let msg = format!(
             "POST {} HTTP/1.1\r\n\
-             Host:{}\r\n\
-             Authorization:Bearer {}\r\n\
-             Content-Type:application/json\r\n\
-             Content-Length:{}\r\n\
-             Connection:close\r\n\
+             content-type: application/json\r\n\
+             authorization: Bearer {}\r\n\
+             user-agent: client/0.1.0\r\n\
+             host: {}\r\n\
+             content-length: {}\r\n\
+             connection: close\r\n\
              \r\n\
              {}",
             env::var("URL").unwrap(),
-            env::var("HOST").unwrap(),
             env::var("OPENAI_API_KEY").unwrap(),
+            env::var("HOST").unwrap(),
             env::var("CONTENT_LENGTH").unwrap(),
             body
         );
@@ -0,0 +1,98 @@
+use ark_ff::PrimeField;
+use ark_r1cs_std::{convert::ToBitsGadget, uint8::UInt8};
+use ark_relations::r1cs::SynthesisError;
+
+use super::{key_expansion::expand_key, sbox::sub_bytes};
+
+pub struct CipherVar<F: PrimeField> {
+    pub round_key_vars: Vec<Vec<UInt8<F>>>,
+}
+
+impl<F: PrimeField> CipherVar<F> {
+    pub fn new(key_vars: &[UInt8<F>]) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            round_key_vars: expand_key(key_vars)?,
+        })
+    }
+
+    pub fn encrypt_block(&self, block: &[UInt8<F>]) -> Result<Vec<UInt8<F>>, SynthesisError> {
+        assert_eq!(block.len(), 16);
+
+        let mut state = add_round_key(block, &self.round_key_vars[0]);
+        for (round, round_key) in self.round_key_vars.iter().enumerate().skip(1) {
+            state = sub_bytes(&state)?;
+            state = (0..16)
+                .map(|i| state[(i + 4 * (i % 4)) % 16].clone())
+                .collect();
+            if round < 10 {
+                state = mix_columns(&state)?;
+            }
+            state = add_round_key(&state, round_key);
+        }
+        Ok(state)
+    }
+}
+
+fn add_round_key<F: PrimeField>(state: &[UInt8<F>], round_key: &[UInt8<F>]) -> Vec<UInt8<F>> {
+    state
+        .iter()
+        .zip(round_key.iter())
+        .map(|(a, b)| a ^ b)
+        .collect()
+}
+
+/// Multiplication by x in GF(2^8), linear in the bits.
+fn xtime<F: PrimeField>(var: &UInt8<F>) -> Result<UInt8<F>, SynthesisError> {
+    let b = var.to_bits_le()?;
+    Ok(UInt8::from_bits_le(&[
+        b[7].clone(),
+        &b[0] ^ &b[7],
+        b[1].clone(),
+        &b[2] ^ &b[7],
+        &b[3] ^ &b[7],
+        b[4].clone(),
+        b[5].clone(),
+        b[6].clone(),
+    ]))
+}
+
+fn mix_columns<F: PrimeField>(state: &[UInt8<F>]) -> Result<Vec<UInt8<F>>, SynthesisError> {
+    let mut output = vec![];
+    for a in state.chunks(4) {
+        let t = &(&a[0] ^ &a[1]) ^ &(&a[2] ^ &a[3]);
+        for i in 0..4 {
+            output.push(&(&a[i] ^ &t) ^ &xtime(&(&a[i] ^ &a[(i + 1) % 4]))?);
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, uint8::UInt8, R1CSVar};
+    use ark_relations::{ns, r1cs::ConstraintSystem};
+
+    use super::CipherVar;
+
+    #[test]
+    fn test_encrypt_block_constraint() {
+        let key = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let block = hex::decode("00112233445566778899aabbccddeeff").unwrap();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let key_vars = key
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc key"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+        let block_vars = block
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc block"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+
+        let cipher = CipherVar::new(&key_vars).unwrap();
+        let output = cipher.encrypt_block(&block_vars).unwrap().value().unwrap();
+        assert_eq!(hex::encode(output), "69c4e0d86a7b0430d8cdb78070b4c55a");
+        assert!(cs.is_satisfied().unwrap());
+    }
+}
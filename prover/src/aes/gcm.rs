@@ -0,0 +1,165 @@
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    convert::ToBitsGadget,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    prelude::Boolean,
+    uint8::UInt8,
+    R1CSVar,
+};
+use ark_relations::{ns, r1cs::SynthesisError};
+
+/// Enough bits for the largest coefficient sum, 504.
+const SUM_BITS: usize = 10;
+
+/// GHASH keyed by `h`, with blocks as 128 coefficients in GCM bit order
+/// (the most significant bit of the first byte is the constant term).
+///
+/// A product in GF(2^128) is computed over the integers: every coefficient
+/// of the reduced product is a sum of at most 504 bit products, and its
+/// parity is the coefficient in GF(2).
+pub struct GhashVar<F: PrimeField> {
+    pub h_bits: Vec<Boolean<F>>,
+}
+
+impl<F: PrimeField> GhashVar<F> {
+    pub fn new(h_vars: &[UInt8<F>]) -> Result<Self, SynthesisError> {
+        assert_eq!(h_vars.len(), 16);
+        Ok(Self {
+            h_bits: to_coefficients(h_vars)?,
+        })
+    }
+
+    pub fn generate_constraints(
+        &self,
+        data_vars: &[UInt8<F>],
+    ) -> Result<Vec<UInt8<F>>, SynthesisError> {
+        assert_eq!(data_vars.len() % 16, 0);
+
+        let mut y = vec![Boolean::FALSE; 128];
+        for block in data_vars.chunks(16) {
+            let x = to_coefficients(block)?
+                .iter()
+                .zip(y.iter())
+                .map(|(a, b)| a ^ b)
+                .collect::<Vec<_>>();
+            y = self.mul(&x)?;
+        }
+
+        Ok(y.chunks(8)
+            .map(|bits| {
+                let mut bits = bits.to_vec();
+                bits.reverse();
+                UInt8::from_bits_le(&bits)
+            })
+            .collect())
+    }
+
+    fn mul(&self, x: &[Boolean<F>]) -> Result<Vec<Boolean<F>>, SynthesisError> {
+        let cs = x.cs().or(self.h_bits.cs());
+
+        let mut products = vec![FpVar::zero(); 255];
+        for (i, a) in x.iter().enumerate() {
+            for (j, b) in self.h_bits.iter().enumerate() {
+                products[i + j] += FpVar::from(a & b);
+            }
+        }
+
+        let mut sums = vec![FpVar::zero(); 128];
+        for (k, row) in reduction_rows().iter().enumerate() {
+            for (j, sum) in sums.iter_mut().enumerate() {
+                if (row >> j) & 1 == 1 {
+                    *sum += &products[k];
+                }
+            }
+        }
+
+        let mut coefficients = vec![];
+        for sum in sums {
+            let value = sum.value().map(|v| v.into_bigint().to_bits_le());
+            let bits = (0..SUM_BITS)
+                .map(|i| {
+                    Boolean::new_witness(ns!(cs, "alloc sum bit"), || {
+                        value.as_ref().map(|b| b[i]).map_err(|e| *e)
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            sum.enforce_equal(&Boolean::le_bits_to_fp(&bits)?)?;
+            coefficients.push(bits[0].clone());
+        }
+        Ok(coefficients)
+    }
+}
+
+/// `x^k mod (x^128 + x^7 + x^2 + x + 1)` for k < 255, bit j being the
+/// coefficient of x^j.
+fn reduction_rows() -> Vec<u128> {
+    let mut rows = vec![1u128];
+    for _ in 1..255 {
+        let last = rows[rows.len() - 1];
+        rows.push(match last >> 127 == 0 {
+            true => last << 1,
+            false => (last << 1) ^ 0x87,
+        });
+    }
+    rows
+}
+
+fn to_coefficients<F: PrimeField>(vars: &[UInt8<F>]) -> Result<Vec<Boolean<F>>, SynthesisError> {
+    let mut bits = vec![];
+    for var in vars {
+        bits.extend(var.to_bits_be()?);
+    }
+    Ok(bits)
+}
+
+/// The gadget counterpart of `native::gcm_mac_data`.
+pub fn gcm_mac_data<F: PrimeField>(
+    aad_vars: &[UInt8<F>],
+    cipher_vars: &[UInt8<F>],
+) -> Vec<UInt8<F>> {
+    let mut data_vars = aad_vars.to_vec();
+    data_vars.resize(aad_vars.len().div_ceil(16) * 16, UInt8::constant(0));
+    data_vars.extend_from_slice(cipher_vars);
+    data_vars.resize(data_vars.len().div_ceil(16) * 16, UInt8::constant(0));
+    for len in [aad_vars.len(), cipher_vars.len()] {
+        data_vars.extend((len as u64 * 8).to_be_bytes().map(UInt8::constant));
+    }
+    data_vars
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, uint8::UInt8, R1CSVar};
+    use ark_relations::{ns, r1cs::ConstraintSystem};
+    use ark_std::{rand::RngCore, test_rng};
+
+    use super::GhashVar;
+    use crate::aes::native::ghash;
+
+    #[test]
+    fn test_ghash_constraint() {
+        let mut rng = test_rng();
+        let mut h = vec![0u8; 16];
+        let mut data = vec![0u8; 48];
+        rng.fill_bytes(&mut h);
+        rng.fill_bytes(&mut data);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let h_vars = h
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc h"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+        let data_vars = data
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc data"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+
+        let ghash_var = GhashVar::new(&h_vars).unwrap();
+        let output = ghash_var.generate_constraints(&data_vars).unwrap();
+        assert_eq!(output.value().unwrap(), ghash(&h, &data));
+        assert!(cs.is_satisfied().unwrap());
+    }
+}
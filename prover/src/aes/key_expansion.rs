@@ -0,0 +1,30 @@
+use ark_ff::PrimeField;
+use ark_r1cs_std::uint8::UInt8;
+use ark_relations::r1cs::SynthesisError;
+
+use super::{native::RCON, sbox::sub_bytes};
+
+/// The 11 AES-128 round keys of `key_vars`.
+pub fn expand_key<F: PrimeField>(
+    key_vars: &[UInt8<F>],
+) -> Result<Vec<Vec<UInt8<F>>>, SynthesisError> {
+    assert_eq!(key_vars.len(), 16);
+
+    let mut words = key_vars.chunks(4).map(|w| w.to_vec()).collect::<Vec<_>>();
+    for i in 4..44 {
+        let mut word = words[i - 1].clone();
+        if i % 4 == 0 {
+            word.rotate_left(1);
+            word = sub_bytes(&word)?;
+            word[0] = &word[0] ^ RCON[i / 4 - 1];
+        }
+        let word = word
+            .iter()
+            .zip(words[i - 4].iter())
+            .map(|(a, b)| a ^ b)
+            .collect();
+        words.push(word);
+    }
+
+    Ok(words.chunks(4).map(|w| w.concat()).collect())
+}
@@ -0,0 +1,120 @@
+use ark_ff::PrimeField;
+use ark_r1cs_std::uint8::UInt8;
+use ark_relations::r1cs::SynthesisError;
+use cipher::CipherVar;
+use gcm::{gcm_mac_data, GhashVar};
+
+pub mod cipher;
+pub mod gcm;
+pub mod key_expansion;
+pub mod native;
+pub mod sbox;
+
+/// AES-128-GCM with a 96-bit nonce, as used by TLS 1.3.
+pub struct AesGcmVar<F: PrimeField> {
+    pub cipher: CipherVar<F>,
+    pub nonce_vars: Vec<UInt8<F>>,
+    pub input_vars: Vec<UInt8<F>>,
+    pub output_vars: Vec<UInt8<F>>,
+}
+
+impl<F: PrimeField> AesGcmVar<F> {
+    pub fn new(
+        key_vars: &[UInt8<F>],
+        nonce_vars: &[UInt8<F>],
+        input_vars: &[UInt8<F>],
+    ) -> Result<Self, SynthesisError> {
+        assert_eq!(key_vars.len(), 16);
+        assert_eq!(nonce_vars.len(), 12);
+
+        Ok(Self {
+            cipher: CipherVar::new(key_vars)?,
+            nonce_vars: nonce_vars.to_vec(),
+            input_vars: input_vars.to_vec(),
+            output_vars: vec![],
+        })
+    }
+
+    /// GCTR over the input, starting at counter 2.
+    pub fn generate_constraints(&mut self) -> Result<(), SynthesisError> {
+        let mut output_vars = vec![];
+        for (i, chunk_vars) in self.input_vars.chunks(16).enumerate() {
+            let key_stream = self
+                .cipher
+                .encrypt_block(&self.counter_block(2 + i as u32))?;
+            for (msg, key) in chunk_vars.iter().zip(key_stream.iter()) {
+                output_vars.push(key ^ msg);
+            }
+        }
+        self.output_vars = output_vars;
+
+        Ok(())
+    }
+
+    /// The tag over `aad_vars` and `cipher_vars`.
+    pub fn tag(
+        &self,
+        aad_vars: &[UInt8<F>],
+        cipher_vars: &[UInt8<F>],
+    ) -> Result<Vec<UInt8<F>>, SynthesisError> {
+        let h_vars = self.cipher.encrypt_block(&vec![UInt8::constant(0); 16])?;
+        let ghash = GhashVar::new(&h_vars)?;
+        let s_vars = ghash.generate_constraints(&gcm_mac_data(aad_vars, cipher_vars))?;
+
+        let mask_vars = self.cipher.encrypt_block(&self.counter_block(1))?;
+        Ok(mask_vars
+            .iter()
+            .zip(s_vars.iter())
+            .map(|(a, b)| a ^ b)
+            .collect())
+    }
+
+    fn counter_block(&self, count: u32) -> Vec<UInt8<F>> {
+        let mut block = self.nonce_vars.clone();
+        block.extend(count.to_be_bytes().map(UInt8::constant));
+        block
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, uint8::UInt8, R1CSVar};
+    use ark_relations::{ns, r1cs::ConstraintSystem};
+
+    use super::AesGcmVar;
+
+    #[test]
+    fn test_aes_gcm_constraint() {
+        let key = hex::decode("feffe9928665731c6d6a8f9467308308").unwrap();
+        let nonce = hex::decode("cafebabefacedbaddecaf888").unwrap();
+        let aad = hex::decode("feedfacedeadbeeffeedfacedeadbeefabaddad2").unwrap();
+        let plain_texts = hex::decode("d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39").unwrap();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let key_vars = key
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc key"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+        let nonce_vars = nonce
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc nonce"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+        let input_vars = plain_texts
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc input"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+        let aad_vars = aad.iter().map(|x| UInt8::constant(*x)).collect::<Vec<_>>();
+
+        let mut aes_gcm = AesGcmVar::new(&key_vars, &nonce_vars, &input_vars).unwrap();
+        aes_gcm.generate_constraints().unwrap();
+        let tag_vars = aes_gcm.tag(&aad_vars, &aes_gcm.output_vars).unwrap();
+
+        assert_eq!(hex::encode(aes_gcm.output_vars.value().unwrap()), "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091");
+        assert_eq!(
+            hex::encode(tag_vars.value().unwrap()),
+            "5bc94fbc3221a5db94fae95ae7121a47"
+        );
+        assert!(cs.is_satisfied().unwrap());
+    }
+}
@@ -0,0 +1,185 @@
+/// The AES S-box (FIPS-197, 5.1.1).
+pub const SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+pub const RCON: [u8; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+/// The 11 AES-128 round keys (FIPS-197, 5.2).
+pub fn expand_key(key: &[u8]) -> Vec<Vec<u8>> {
+    assert_eq!(key.len(), 16);
+
+    let mut words = key.chunks(4).map(|w| w.to_vec()).collect::<Vec<_>>();
+    for i in 4..44 {
+        let mut word = words[i - 1].clone();
+        if i % 4 == 0 {
+            word.rotate_left(1);
+            word = word.iter().map(|b| SBOX[*b as usize]).collect();
+            word[0] ^= RCON[i / 4 - 1];
+        }
+        let word = word
+            .iter()
+            .zip(words[i - 4].iter())
+            .map(|(a, b)| a ^ b)
+            .collect();
+        words.push(word);
+    }
+
+    words.chunks(4).map(|w| w.concat()).collect()
+}
+
+pub fn xtime(a: u8) -> u8 {
+    match a & 0x80 == 0 {
+        true => a << 1,
+        false => (a << 1) ^ 0x1b,
+    }
+}
+
+pub fn encrypt_block(round_keys: &[Vec<u8>], block: &[u8]) -> Vec<u8> {
+    assert_eq!(block.len(), 16);
+
+    let mut state = block
+        .iter()
+        .zip(round_keys[0].iter())
+        .map(|(a, b)| a ^ b)
+        .collect::<Vec<_>>();
+    for (round, round_key) in round_keys.iter().enumerate().skip(1) {
+        state = state.iter().map(|b| SBOX[*b as usize]).collect();
+        state = (0..16).map(|i| state[(i + 4 * (i % 4)) % 16]).collect();
+        if round < 10 {
+            for column in state.chunks_mut(4) {
+                let t = column[0] ^ column[1] ^ column[2] ^ column[3];
+                let a = column.to_vec();
+                for i in 0..4 {
+                    column[i] ^= t ^ xtime(a[i] ^ a[(i + 1) % 4]);
+                }
+            }
+        }
+        state = state
+            .iter()
+            .zip(round_key.iter())
+            .map(|(a, b)| a ^ b)
+            .collect();
+    }
+    state
+}
+
+/// The counter block `nonce || count` of GCM with a 96-bit nonce.
+pub fn counter_block(nonce: &[u8], count: u32) -> Vec<u8> {
+    assert_eq!(nonce.len(), 12);
+
+    let mut block = nonce.to_vec();
+    block.extend(count.to_be_bytes());
+    block
+}
+
+/// Encrypt or decrypt `input` in GCTR mode, starting at counter 2 as the
+/// GCM payload does (counter 1 masks the tag).
+pub fn gctr(key: &[u8], nonce: &[u8], input: &[u8]) -> Vec<u8> {
+    let round_keys = expand_key(key);
+    let mut output = vec![];
+    for (i, chunk) in input.chunks(16).enumerate() {
+        let key_stream = encrypt_block(&round_keys, &counter_block(nonce, 2 + i as u32));
+        output.extend(chunk.iter().zip(key_stream.iter()).map(|(m, k)| m ^ k));
+    }
+    output
+}
+
+/// Multiplication in GF(2^128) with the GCM bit order (SP 800-38D, 6.3).
+pub fn gf128_mul(x: u128, y: u128) -> u128 {
+    let mut z = 0;
+    let mut v = y;
+    for i in (0..128).rev() {
+        if (x >> i) & 1 == 1 {
+            z ^= v;
+        }
+        v = match v & 1 == 0 {
+            true => v >> 1,
+            false => (v >> 1) ^ (0xe1 << 120),
+        };
+    }
+    z
+}
+
+pub fn ghash(h: &[u8], data: &[u8]) -> Vec<u8> {
+    assert_eq!(data.len() % 16, 0);
+
+    let h = u128::from_be_bytes(h.try_into().unwrap());
+    let mut y = 0;
+    for block in data.chunks(16) {
+        y = gf128_mul(y ^ u128::from_be_bytes(block.try_into().unwrap()), h);
+    }
+    y.to_be_bytes().to_vec()
+}
+
+/// The GHASH input of GCM: the padded additional data and cipher texts,
+/// followed by both lengths in bits.
+pub fn gcm_mac_data(aad: &[u8], cipher_texts: &[u8]) -> Vec<u8> {
+    let mut data = aad.to_vec();
+    data.resize(aad.len().div_ceil(16) * 16, 0);
+    data.extend_from_slice(cipher_texts);
+    data.resize(data.len().div_ceil(16) * 16, 0);
+    data.extend((aad.len() as u64 * 8).to_be_bytes());
+    data.extend((cipher_texts.len() as u64 * 8).to_be_bytes());
+    data
+}
+
+/// The AES-GCM tag of `cipher_texts`.
+pub fn aead_tag(key: &[u8], nonce: &[u8], aad: &[u8], cipher_texts: &[u8]) -> Vec<u8> {
+    let round_keys = expand_key(key);
+    let h = encrypt_block(&round_keys, &[0; 16]);
+    let s = ghash(&h, &gcm_mac_data(aad, cipher_texts));
+    encrypt_block(&round_keys, &counter_block(nonce, 1))
+        .iter()
+        .zip(s.iter())
+        .map(|(a, b)| a ^ b)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{aead_tag, encrypt_block, expand_key, gctr};
+
+    #[test]
+    fn test_encrypt_block() {
+        // FIPS-197, C.1
+        let key = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let block = hex::decode("00112233445566778899aabbccddeeff").unwrap();
+        let round_keys = expand_key(&key);
+        assert_eq!(
+            hex::encode(encrypt_block(&round_keys, &block)),
+            "69c4e0d86a7b0430d8cdb78070b4c55a"
+        );
+    }
+
+    #[test]
+    fn test_gcm() {
+        // GCM spec, test case 4
+        let key = hex::decode("feffe9928665731c6d6a8f9467308308").unwrap();
+        let nonce = hex::decode("cafebabefacedbaddecaf888").unwrap();
+        let aad = hex::decode("feedfacedeadbeeffeedfacedeadbeefabaddad2").unwrap();
+        let plain_texts = hex::decode("d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39").unwrap();
+
+        let cipher_texts = gctr(&key, &nonce, &plain_texts);
+        assert_eq!(hex::encode(&cipher_texts), "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091");
+        assert_eq!(
+            hex::encode(aead_tag(&key, &nonce, &aad, &cipher_texts)),
+            "5bc94fbc3221a5db94fae95ae7121a47"
+        );
+    }
+}
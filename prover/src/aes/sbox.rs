@@ -0,0 +1,23 @@
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    convert::ToBitsGadget, fields::fp::FpVar, select::CondSelectGadget, uint8::UInt8,
+};
+use ark_relations::r1cs::SynthesisError;
+
+use super::native::SBOX;
+
+/// Look `var` up in the S-box: the first level of the selection tree picks
+/// between constants for free, so it costs 127 constraints plus the bits of
+/// the result.
+pub fn sub_byte<F: PrimeField>(var: &UInt8<F>) -> Result<UInt8<F>, SynthesisError> {
+    let table = SBOX
+        .iter()
+        .map(|x| FpVar::Constant(F::from(*x)))
+        .collect::<Vec<_>>();
+    let output = FpVar::conditionally_select_power_of_two_vector(&var.to_bits_be()?, &table)?;
+    Ok(UInt8::from_fp(&output)?.0)
+}
+
+pub fn sub_bytes<F: PrimeField>(vars: &[UInt8<F>]) -> Result<Vec<UInt8<F>>, SynthesisError> {
+    vars.iter().map(sub_byte).collect()
+}
@@ -0,0 +1,52 @@
+use crate::{json::JsonPath, provider::Layout};
+
+/// The API version pinned in every request.
+pub const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Messages: an `x-api-key`, and the system prompt in the top-level
+/// `system`, apart from `messages`.
+pub fn layout() -> Layout {
+    Layout {
+        api_key_header: "x-api-key",
+        api_key_prefix: b"",
+        pinned_headers: vec![("anthropic-version", ANTHROPIC_VERSION.as_bytes().to_vec())],
+        prompt: JsonPath::parse("system"),
+        prompt_role: None,
+        message_paths: |i| {
+            [
+                JsonPath::parse(&format!("messages[{i}].role")),
+                JsonPath::parse(&format!("messages[{i}].content")),
+            ]
+        },
+        prompt_excluded: vec![],
+        message_excluded: |_| vec![],
+        tool_paths: ["tools", "tool_choice"].map(JsonPath::parse).to_vec(),
+        max_json_depth: 4,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{request::test::request_constraints, template::RequestTemplate};
+
+    #[test]
+    fn test_anthropic_request() {
+        let template =
+            RequestTemplate::from_toml(include_str!("../../templates/anthropic.toml")).unwrap();
+        let body = r#"{"model":"claude-3-5-haiku-latest","max_tokens":256,"system":"you are a zypher girl!","messages":[{"role":"user","content":"what is your name?"},{"role":"assistant","content":"zypher"}]}"#;
+        let data = format!("POST /v1/messages HTTP/1.1\r\nhost: api.anthropic.com\r\nx-api-key: sk-ant-api03\r\nanthropic-version: 2023-06-01\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{body}", body.len());
+
+        let (prompt, messages, satisfied) = request_constraints(&template, data.as_bytes());
+        assert!(satisfied);
+        assert_eq!(prompt, b"you are a zypher girl!");
+        assert_eq!(
+            messages[0],
+            (b"user".to_vec(), b"what is your name?".to_vec())
+        );
+        assert_eq!(messages[1], (b"assistant".to_vec(), b"zypher".to_vec()));
+
+        // the version is pinned
+        let data = data.replace("2023-06-01", "2024-06-01");
+        assert!(!request_constraints(&template, data.as_bytes()).2);
+    }
+}
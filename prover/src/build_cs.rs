@@ -1,114 +1,597 @@
-use std::env;
+use std::marker::PhantomData;
 
 use ark_bn254::Fr;
-use ark_r1cs_std::{
-    alloc::AllocVar,
-    eq::EqGadget,
-    fields::{fp::FpVar, FieldVar},
-    prelude::ToBitsGadget,
-    uint32::UInt32,
-    uint8::UInt8,
-    R1CSVar,
-};
+use ark_ff::Zero;
+use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, fields::fp::FpVar, uint8::UInt8, R1CSVar};
 use ark_relations::{
     ns,
     r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
 };
 
 use crate::{
-    chacha20::ChaCha20Var,
-    mimc::{
-        bn254::{constraint::MimcBn254Var, MimcBn254},
-        MiMC,
+    commitment::{
+        commit_api_key, commit_api_key_var, commit_cipher, commit_cipher_var, commit_message,
+        commit_message_var, commit_prompt, commit_prompt_var, commit_secret, commit_secret_var,
+        commit_tools, commit_tools_var, CommitmentGadget,
     },
-    openai::req::{traits::ReqConstraint, ReqVar},
-    utils::compress_var,
+    json::JsonVar,
+    mimc::bn254::MimcBn254,
+    prover::PublicInputs,
+    record::{decrypt_records, Record, RecordVar},
+    request::{traits::ReqConstraint, ReqVar},
+    suite::CipherSuite,
+    template::RequestTemplate,
 };
 
-pub struct ZkPrompt {
-    pub cipher_texts: Vec<u8>,
-    pub key: Vec<u8>,
-    pub nonce: Vec<u8>,
-    pub count: u32,
+/// The values of a request bound by public inputs, as computed in circuit
+/// by `ZkPrompt::request_vars`.
+pub(crate) struct RequestVars {
+    pub prompt_commitment: FpVar<Fr>,
+    pub cipher_commitment: FpVar<Fr>,
+    /// The API key, message and tools commitments, then the disclosed
+    /// values and numbers, in the order of `PublicInputs`.
+    pub disclosure_vars: Vec<FpVar<Fr>>,
 }
 
-impl ConstraintSynthesizer<Fr> for ZkPrompt {
-    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
-        let mut key_vars = vec![];
-        for chunk in self.key.chunks(4) {
-            let val = u32::from_le_bytes(chunk.try_into().unwrap());
-            let var = UInt32::new_witness(ns!(cs, "alloc key"), || Ok(val))?;
-            key_vars.push(var);
+pub struct ZkPrompt<C: CommitmentGadget = MimcBn254> {
+    pub template: RequestTemplate,
+    pub suite: CipherSuite,
+    /// The application data records carrying the request, numbered from
+    /// `seq`; their plain texts are parsed as one request.
+    pub records: Vec<Record>,
+    /// The client application traffic secret, from which the AEAD key and
+    /// iv are derived.
+    pub secret: Vec<u8>,
+    /// The sequence number of the first record within the session, public.
+    pub seq: u64,
+    /// Public bounds of the prompt length, at most `max_prompt_len` of the
+    /// template; a zero `prompt_len_min` leaves it unbounded below.
+    pub prompt_len_min: usize,
+    pub prompt_len_max: usize,
+    /// Blinding factor of the prompt commitment, kept by the prover to open
+    /// it later with `open_commitment`.
+    pub prompt_salt: Fr,
+    /// Blinding factors of the message commitments, one per `num_messages`
+    /// of the template, so that each message opens alone.
+    pub message_salts: Vec<Fr>,
+    /// Blinding factor of the tools commitment, if the template asks for it.
+    pub tools_salt: Fr,
+    /// The hash used for every commitment.
+    pub commitment: PhantomData<C>,
+}
+
+impl<C: CommitmentGadget> ZkPrompt<C> {
+    /// A circuit with the shape of a request in records of `record_lens`
+    /// bytes and an all zero witness, used for key generation.
+    pub fn empty(template: &RequestTemplate, suite: CipherSuite, record_lens: &[usize]) -> Self {
+        Self {
+            template: template.clone(),
+            suite,
+            records: record_lens
+                .iter()
+                .map(|len| Record {
+                    cipher_texts: vec![0; *len],
+                    tag: vec![0; 16],
+                })
+                .collect(),
+            secret: vec![0; 32],
+            seq: 0,
+            prompt_len_min: 0,
+            prompt_len_max: template.max_prompt_len,
+            prompt_salt: Fr::zero(),
+            message_salts: vec![Fr::zero(); template.num_messages],
+            tools_salt: Fr::zero(),
+            commitment: PhantomData,
+        }
+    }
+
+    /// Compute the public inputs natively, as `generate_constraints` does,
+    /// failing if the request would not satisfy it.
+    pub fn public_inputs(&self) -> Result<PublicInputs, SynthesisError> {
+        let plain_texts = decrypt_records(self.suite, &self.secret, self.seq, &self.records);
+
+        let req = ReqVar::<Fr>::new(&[], &self.template);
+        let prompt = req
+            .prompt(&plain_texts)
+            .ok_or(SynthesisError::Unsatisfiable)?;
+        let messages = req
+            .messages(&plain_texts)
+            .ok_or(SynthesisError::Unsatisfiable)?;
+        let api_key_commitment = match self.template.bind_api_key {
+            true => {
+                let api_key = req
+                    .api_key_range(&plain_texts)
+                    .ok_or(SynthesisError::Unsatisfiable)?;
+                Some(commit_api_key::<C>(&plain_texts[api_key]))
+            }
+            false => None,
+        };
+
+        Ok(PublicInputs {
+            seq: self.seq,
+            prompt_len_min: self.prompt_len_min as u64,
+            prompt_len_max: self.prompt_len_max as u64,
+            prompt_commitment: commit_prompt::<C>(
+                prompt,
+                self.template.max_prompt_len,
+                self.prompt_salt,
+            ),
+            cipher_commitment: commit_cipher::<C>(
+                &self
+                    .records
+                    .iter()
+                    .flat_map(Record::wire)
+                    .collect::<Vec<_>>(),
+            ),
+            secret_commitment: commit_secret::<C>(&self.secret),
+            api_key_commitment,
+            message_commitments: messages
+                .iter()
+                .zip(self.message_salts()?)
+                .map(|((role, content), salt)| {
+                    commit_message::<C>(role, content, self.template.max_message_len, *salt)
+                })
+                .collect(),
+            tools_commitment: self.template.tools.as_ref().map(|tools| {
+                commit_tools::<C>(&req.tools(&plain_texts), tools.max_len, self.tools_salt)
+            }),
+            disclosed: req.disclosed(&plain_texts),
+            numbers: req
+                .numbers(&plain_texts)
+                .ok_or(SynthesisError::Unsatisfiable)?,
+        })
+    }
+}
+
+impl<C: CommitmentGadget> ZkPrompt<C> {
+    /// The message salts, failing unless there is one per `num_messages`
+    /// of the template.
+    fn message_salts(&self) -> Result<&[Fr], SynthesisError> {
+        match self.message_salts.len() == self.template.num_messages {
+            true => Ok(&self.message_salts),
+            false => Err(SynthesisError::Unsatisfiable),
+        }
+    }
+
+    /// Allocate the public bounds of the prompt length.
+    fn prompt_len_bound_vars(
+        &self,
+        cs: ConstraintSystemRef<Fr>,
+    ) -> Result<(FpVar<Fr>, FpVar<Fr>), SynthesisError> {
+        let min_var = FpVar::new_input(ns!(cs, "public prompt len min"), || {
+            Ok(Fr::from(self.prompt_len_min as u64))
+        })?;
+        let max_var = FpVar::new_input(ns!(cs, "public prompt len max"), || {
+            Ok(Fr::from(self.prompt_len_max as u64))
+        })?;
+        Ok((min_var, max_var))
+    }
+
+    /// The commitments to the messages of the request, in the order of
+    /// `PublicInputs::message_commitments`.
+    fn message_commitment_vars(
+        &self,
+        cs: ConstraintSystemRef<Fr>,
+        hash_var: &C::Var,
+        req_var: &ReqVar<Fr>,
+        body: &JsonVar<Fr>,
+    ) -> Result<Vec<FpVar<Fr>>, SynthesisError> {
+        let mut commitments = vec![];
+        for (message, salt) in req_var
+            .message_vars(body)?
+            .iter()
+            .zip(self.message_salts()?)
+        {
+            let salt_var = FpVar::new_witness(ns!(cs, "alloc message salt"), || Ok(*salt))?;
+            commitments.push(commit_message_var::<C>(
+                hash_var,
+                &message.role,
+                &message.content,
+                &salt_var,
+            )?);
+        }
+        Ok(commitments)
+    }
+
+    /// The commitment to the tool definitions of the request, if the
+    /// template asks for it.
+    fn tools_commitment_var(
+        &self,
+        cs: ConstraintSystemRef<Fr>,
+        hash_var: &C::Var,
+        req_var: &ReqVar<Fr>,
+        body: &JsonVar<Fr>,
+    ) -> Result<Option<FpVar<Fr>>, SynthesisError> {
+        if self.template.tools.is_none() {
+            return Ok(None);
+        }
+        let salt_var = FpVar::new_witness(ns!(cs, "alloc tools salt"), || Ok(self.tools_salt))?;
+        let tools = req_var.tool_vars(body)?;
+        Ok(Some(commit_tools_var::<C>(hash_var, &tools, &salt_var)?))
+    }
+
+    /// Decrypt the records under `secret_vars` from `seq_var` and locate the
+    /// request, allocating the public bounds of its prompt length first.
+    pub(crate) fn request_vars(
+        &self,
+        cs: ConstraintSystemRef<Fr>,
+        hash_var: &C::Var,
+        secret_vars: &[UInt8<Fr>],
+        seq_var: &FpVar<Fr>,
+    ) -> Result<RequestVars, SynthesisError> {
+        let (min_var, max_var) = self.prompt_len_bound_vars(cs.clone())?;
+        let records =
+            RecordVar::sequence(cs.clone(), self.suite, secret_vars, seq_var, &self.records)?;
+        let mut plain_vars = vec![];
+        for record in records.iter() {
+            plain_vars.extend(record.content_vars(self.template.record_padding)?);
         }
 
-        let mut nonce_vars = vec![];
-        for chunk in self.nonce.chunks(4) {
-            let val = u32::from_le_bytes(chunk.try_into().unwrap());
-            let var = UInt32::new_witness(ns!(cs, "alloc key"), || Ok(val))?;
-            nonce_vars.push(var);
+        let req_var = ReqVar::new(&plain_vars, &self.template);
+        let head = req_var.generate_constraints()?;
+        let body = req_var.body_var(&head)?;
+        let prompt = req_var.prompt_var(&body)?;
+        prompt.enforce_len_within(&min_var, &max_var)?;
+
+        let salt_var = FpVar::new_witness(ns!(cs, "alloc salt"), || Ok(self.prompt_salt))?;
+        let wire_vars = records
+            .iter()
+            .flat_map(RecordVar::wire_vars)
+            .collect::<Vec<_>>();
+
+        let mut disclosure_vars = vec![];
+        if self.template.bind_api_key {
+            let api_key = req_var.api_key_var(&head)?;
+            disclosure_vars.push(commit_api_key_var::<C>(hash_var, &api_key)?);
         }
+        disclosure_vars.extend(self.message_commitment_vars(
+            cs.clone(),
+            hash_var,
+            &req_var,
+            &body,
+        )?);
+        disclosure_vars.extend(self.tools_commitment_var(cs.clone(), hash_var, &req_var, &body)?);
+        disclosure_vars.extend(req_var.disclosed_vars(&body)?.into_iter().flatten());
+        disclosure_vars.extend(req_var.number_vars(&body)?);
 
-        let qr_constant_vars = vec![
-            UInt32::new_constant(ns!(cs, "alloc constant"), 0x61707865).unwrap(),
-            UInt32::new_constant(ns!(cs, "alloc constant"), 0x3320646e).unwrap(),
-            UInt32::new_constant(ns!(cs, "alloc constant"), 0x79622d32).unwrap(),
-            UInt32::new_constant(ns!(cs, "alloc constant"), 0x6b206574).unwrap(),
-        ];
+        Ok(RequestVars {
+            prompt_commitment: commit_prompt_var::<C>(hash_var, &prompt, &salt_var)?,
+            cipher_commitment: commit_cipher_var::<C>(hash_var, &wire_vars)?,
+            disclosure_vars,
+        })
+    }
+}
 
-        let count_var = UInt32::new_witness(ns!(cs, "alloc count"), || Ok(self.count))?;
-        let cipher_vars = self
-            .cipher_texts
+impl<C: CommitmentGadget> ConstraintSynthesizer<Fr> for ZkPrompt<C> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let seq_var = FpVar::new_input(ns!(cs, "public seq"), || Ok(Fr::from(self.seq)))?;
+        let secret_vars = self
+            .secret
             .iter()
-            .map(|x| UInt8::new_witness(ns!(cs, "alloc cipher"), || Ok(*x)).unwrap())
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc secret"), || Ok(*x)).unwrap())
             .collect::<Vec<_>>();
 
-        let mut chacha20 = ChaCha20Var::new(
-            &qr_constant_vars,
-            &key_vars,
-            &nonce_vars,
-            count_var,
-            &cipher_vars,
-        );
-        chacha20.generate_constraints()?;
+        let hash_var = C::new_var(cs.clone());
+        let request = self.request_vars(cs.clone(), &hash_var, &secret_vars, &seq_var)?;
+        let secret_commitment = commit_secret_var::<C>(&hash_var, &secret_vars)?;
+
+        let pi_prompt_commitment = FpVar::new_input(ns!(cs, "public prompt"), || {
+            request.prompt_commitment.value()
+        })?;
+        pi_prompt_commitment.enforce_equal(&request.prompt_commitment)?;
 
-        let prompt_len = env::var("PROMPT_LEN").unwrap().parse().unwrap();
-        let req_var = ReqVar::new(&chacha20.output_vars, prompt_len);
-        req_var.generate_constraints()?;
+        let pi_cipher_commitment = FpVar::new_input(ns!(cs, "public cipher"), || {
+            request.cipher_commitment.value()
+        })?;
+        pi_cipher_commitment.enforce_equal(&request.cipher_commitment)?;
 
-        let start = req_var.prompt_start();
-        let end = start + prompt_len;
-        let prompt = &chacha20.output_vars[start..end];
+        let pi_secret_commitment =
+            FpVar::new_input(ns!(cs, "public secret"), || secret_commitment.value())?;
+        pi_secret_commitment.enforce_equal(&secret_commitment)?;
 
-        let mut round_constant_vars = vec![];
-        for c in MimcBn254::ROUND_KEYS {
-            round_constant_vars.push(FpVar::new_constant(ns!(cs, "alloc round keys"), c).unwrap());
+        for disclosure in request.disclosure_vars {
+            let pi_disclosure =
+                FpVar::new_input(ns!(cs, "public disclosure"), || disclosure.value())?;
+            pi_disclosure.enforce_equal(&disclosure)?;
         }
-        let mimc_var = MimcBn254Var::new(1, &round_constant_vars, FpVar::zero());
 
-        let mut prompt_bits = vec![];
-        for p in prompt {
-            prompt_bits.extend(p.to_bits_be()?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::marker::PhantomData;
+
+    use ark_bn254::Fr;
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+    use ark_std::{test_rng, UniformRand};
+
+    use super::ZkPrompt;
+    use crate::{
+        commitment::{commit_api_key, commit_message, commit_tools, open_commitment},
+        mimc::bn254::MimcBn254,
+        record::{decrypt, encrypt, encrypt_records, inner_plaintext, Record, MAX_RECORD_LEN},
+        slice::pack_slice,
+        suite::CipherSuite,
+        template::{RequestTemplate, ToolsCommitment},
+    };
+
+    pub(crate) fn sample_prompt(suite: CipherSuite) -> ZkPrompt {
+        sample_prompt_records(suite, MAX_RECORD_LEN)
+    }
+
+    /// The sample request split in records of at most `max_record_len` bytes.
+    pub(crate) fn sample_prompt_records(suite: CipherSuite, max_record_len: usize) -> ZkPrompt {
+        let template =
+            RequestTemplate::from_toml(include_str!("../templates/openai.toml")).unwrap();
+
+        let plain_texts = hex::decode("504f5354202f76312f636861742f636f6d706c6574696f6e7320485454502f312e310d0a486f73743a6170692e6f70656e61692e636f6d0d0a417574686f72697a6174696f6e3a42656172657220736b2d737663616363740d0a436f6e74656e742d547970653a6170706c69636174696f6e2f6a736f6e0d0a436f6e74656e742d4c656e6774683a3135350d0a436f6e6e656374696f6e3a636c6f73650d0a0d0a7b226d65737361676573223a5b7b22726f6c65223a2273797374656d222c22636f6e74656e74223a22796f75206172652061207a7970686572206769726c21227d2c7b22726f6c65223a2275736572222c22636f6e74656e74223a227768617420697320796f7572206e616d653f227d5d2c226d6f64656c223a226770742d346f2d6d696e69222c2274656d7065726174757265223a20302e377d").unwrap();
+        // the traffic secrets of the client tests
+        let secret = match suite {
+            CipherSuite::Chacha20Poly1305Sha256 => {
+                "ac49f185f79856dad7a170ce8acc3f297229f6392ce3b107e30a560b23922cca"
+            }
+            CipherSuite::Aes128GcmSha256 => {
+                "4164bcc581ef89891256d9a7752205fb1c0504920eaa027acb5b9085033933a7"
+            }
+        };
+        let secret = hex::decode(secret).unwrap();
+        let seq = 1;
+        let records = encrypt_records(
+            suite,
+            &secret,
+            seq,
+            &plain_texts,
+            max_record_len,
+            template.record_padding,
+        );
+        let mut rng = test_rng();
+
+        ZkPrompt {
+            template,
+            suite,
+            records,
+            secret,
+            seq,
+            prompt_len_min: 16,
+            prompt_len_max: 32,
+            prompt_salt: Fr::rand(&mut rng),
+            message_salts: vec![Fr::rand(&mut rng), Fr::rand(&mut rng)],
+            tools_salt: Fr::rand(&mut rng),
+            commitment: PhantomData,
         }
-        let compress_prompt = compress_var(&prompt_bits, 250)?;
-        let prompt_commitment = mimc_var.generate_constraints(&compress_prompt)[0].clone();
+    }
+
+    #[test]
+    fn test_zk_prompt_tag() {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        sample_prompt(CipherSuite::Chacha20Poly1305Sha256)
+            .generate_constraints(cs.clone())
+            .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        let mut circuit = sample_prompt(CipherSuite::Chacha20Poly1305Sha256);
+        circuit.records[0].tag[0] ^= 1;
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+
+        // the same record claimed under another sequence number
+        let mut circuit = sample_prompt(CipherSuite::Chacha20Poly1305Sha256);
+        circuit.seq += 1;
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_zk_prompt_records() {
+        let circuit = sample_prompt_records(CipherSuite::Chacha20Poly1305Sha256, 128);
+        assert_eq!(circuit.records.len(), 3);
+        let public_inputs = circuit.public_inputs().unwrap();
+        assert_eq!(
+            public_inputs.prompt_commitment,
+            sample_prompt(CipherSuite::Chacha20Poly1305Sha256)
+                .public_inputs()
+                .unwrap()
+                .prompt_commitment
+        );
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(
+            cs.borrow().unwrap().instance_assignment[1..],
+            public_inputs.to_vec()
+        );
+
+        // records out of order
+        let mut circuit = sample_prompt_records(CipherSuite::Chacha20Poly1305Sha256, 128);
+        circuit.records.swap(0, 1);
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
 
-        let mut cipher_bits = vec![];
-        for c in cipher_vars {
-            cipher_bits.extend(c.to_bits_be()?);
+    /// Replace the only record of `circuit` by `inner` sealed alike.
+    fn reseal(circuit: &mut ZkPrompt, inner: &[u8]) {
+        let (cipher_texts, tag) = encrypt(circuit.suite, &circuit.secret, circuit.seq, inner);
+        circuit.records = vec![Record { cipher_texts, tag }];
+    }
+
+    #[test]
+    fn test_inner_plaintext() {
+        let circuit = sample_prompt(CipherSuite::Chacha20Poly1305Sha256);
+        let inner = decrypt(
+            circuit.suite,
+            &circuit.secret,
+            circuit.seq,
+            &circuit.records[0].cipher_texts,
+        );
+        let content = &inner[..inner.len() - 1];
+
+        let mut circuit = sample_prompt(CipherSuite::Chacha20Poly1305Sha256);
+        circuit.template.record_padding = 3;
+        reseal(&mut circuit, &inner_plaintext(content, 3));
+        let public_inputs = circuit.public_inputs().unwrap();
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(
+            cs.borrow().unwrap().instance_assignment[1..],
+            public_inputs.to_vec()
+        );
+
+        // an alert record, then a non-zero padding
+        for (i, b) in [(content.len(), 0x15), (content.len() + 2, 1)] {
+            let mut circuit = sample_prompt(CipherSuite::Chacha20Poly1305Sha256);
+            circuit.template.record_padding = 3;
+            let mut inner = inner_plaintext(content, 3);
+            inner[i] = b;
+            reseal(&mut circuit, &inner);
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            circuit.generate_constraints(cs.clone()).unwrap();
+            assert!(!cs.is_satisfied().unwrap());
         }
-        let compress_cipher = compress_var(&cipher_bits, 250)?;
-        let cipher_commitment = mimc_var.generate_constraints(&compress_cipher)[0].clone();
+    }
 
-        let pi_prompt_commitment =
-            FpVar::new_input(ns!(cs, "public prompt"), || prompt_commitment.value())?;
-        pi_prompt_commitment.enforce_equal(&prompt_commitment)?;
+    #[test]
+    fn test_public_inputs_error() {
+        // the sample request has two messages only
+        let mut circuit = sample_prompt(CipherSuite::Chacha20Poly1305Sha256);
+        circuit.template.num_messages = 3;
+        circuit.message_salts.push(Fr::from(3u8));
+        assert!(circuit.public_inputs().is_err());
 
-        let pi_cipher_commitment =
-            FpVar::new_input(ns!(cs, "public cipher"), || cipher_commitment.value())?;
-        pi_cipher_commitment.enforce_equal(&cipher_commitment)?;
+        // a template expecting another API key length
+        let mut circuit = sample_prompt(CipherSuite::Chacha20Poly1305Sha256);
+        circuit.template.api_key_len += 1;
+        assert!(circuit.public_inputs().is_err());
 
-        println!("cs size:{}", cs.num_constraints());
+        // a salt short, natively and in circuit
+        let mut circuit = sample_prompt(CipherSuite::Chacha20Poly1305Sha256);
+        circuit.message_salts.pop();
+        assert!(circuit.public_inputs().is_err());
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        assert!(circuit.generate_constraints(cs).is_err());
+    }
 
-        Ok(())
+    #[test]
+    fn test_api_key_commitment() {
+        let public_inputs = sample_prompt(CipherSuite::Chacha20Poly1305Sha256)
+            .public_inputs()
+            .unwrap();
+        assert_eq!(
+            public_inputs.api_key_commitment,
+            Some(commit_api_key::<MimcBn254>(b"sk-svcacct"))
+        );
+    }
+
+    #[test]
+    fn test_disclosed() {
+        let public_inputs = sample_prompt(CipherSuite::Chacha20Poly1305Sha256)
+            .public_inputs()
+            .unwrap();
+        assert_eq!(
+            public_inputs.disclosed,
+            vec![pack_slice(b"\"gpt-4o-mini\"", 32), pack_slice(b"0.7", 8)]
+        );
+    }
+
+    #[test]
+    fn test_numbers() {
+        let public_inputs = sample_prompt(CipherSuite::Chacha20Poly1305Sha256)
+            .public_inputs()
+            .unwrap();
+        // the sample temperature of 0.7 with two decimals
+        assert_eq!(public_inputs.numbers, vec![70]);
+    }
+
+    #[test]
+    fn test_message_commitments() {
+        let circuit = sample_prompt(CipherSuite::Chacha20Poly1305Sha256);
+        let public_inputs = circuit.public_inputs().unwrap();
+        let salts = &circuit.message_salts;
+        assert_eq!(
+            public_inputs.message_commitments,
+            vec![
+                commit_message::<MimcBn254>(b"system", b"you are a zypher girl!", 64, salts[0]),
+                commit_message::<MimcBn254>(b"user", b"what is your name?", 64, salts[1]),
+            ]
+        );
+        // the role is committed as well
+        assert_ne!(
+            public_inputs.message_commitments[1],
+            commit_message::<MimcBn254>(b"system", b"what is your name?", 64, salts[1])
+        );
+    }
+
+    #[test]
+    fn test_tools_commitment() {
+        let mut circuit = sample_prompt(CipherSuite::Chacha20Poly1305Sha256);
+        assert_eq!(circuit.public_inputs().unwrap().tools_commitment, None);
+
+        circuit.template.tools = Some(ToolsCommitment {
+            max_len: 64,
+            max_json_depth: 4,
+        });
+        let public_inputs = circuit.public_inputs().unwrap();
+        // the sample request defines no tools
+        assert_eq!(
+            public_inputs.tools_commitment,
+            Some(commit_tools::<MimcBn254>(
+                &[b"", b"", b""],
+                64,
+                circuit.tools_salt
+            ))
+        );
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(
+            cs.borrow().unwrap().instance_assignment[1..],
+            public_inputs.to_vec()
+        );
+    }
+
+    #[test]
+    fn test_open_commitment() {
+        let circuit = sample_prompt(CipherSuite::Chacha20Poly1305Sha256);
+        let public_inputs = circuit.public_inputs().unwrap();
+        let prompt = b"you are a zypher girl!";
+        assert_eq!(
+            open_commitment::<MimcBn254>(prompt, &circuit.template, circuit.prompt_salt),
+            public_inputs.prompt_commitment
+        );
+        assert_ne!(
+            open_commitment::<MimcBn254>(
+                prompt,
+                &circuit.template,
+                circuit.prompt_salt + Fr::from(1u8)
+            ),
+            public_inputs.prompt_commitment
+        );
+    }
+
+    #[test]
+    fn test_prompt_len_bounds() {
+        // the prompt is 22 bytes
+        for (min, max, satisfied) in [(22, 22, true), (0, 21, false), (23, 64, false)] {
+            let mut circuit = sample_prompt(CipherSuite::Chacha20Poly1305Sha256);
+            circuit.prompt_len_min = min;
+            circuit.prompt_len_max = max;
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            circuit.generate_constraints(cs.clone()).unwrap();
+            assert_eq!(cs.is_satisfied().unwrap(), satisfied);
+        }
+    }
+
+    #[test]
+    fn test_zk_prompt_aes_128_gcm() {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        sample_prompt(CipherSuite::Aes128GcmSha256)
+            .generate_constraints(cs.clone())
+            .unwrap();
+        assert!(cs.is_satisfied().unwrap());
     }
 }
@@ -3,6 +3,7 @@ use ark_r1cs_std::{uint32::UInt32, uint8::UInt8, R1CSVar};
 use ark_relations::r1cs::SynthesisError;
 use round::Round;
 
+pub mod native;
 pub mod quarter_round;
 pub mod round;
 
@@ -61,6 +62,18 @@ impl<F: PrimeField> ChaCha20Var<F> {
         Ok(())
     }
 
+    /// The key stream block at `count_var`, e.g. block 0 which keys Poly1305.
+    pub fn key_stream(&self, count_var: &UInt32<F>) -> Result<Vec<UInt8<F>>, SynthesisError> {
+        let mut state_vars = self.qr_constant_vars.clone();
+        state_vars.extend_from_slice(&self.key_vars);
+        state_vars.push(count_var.clone());
+        state_vars.extend_from_slice(&self.nonce_vars);
+
+        let mut round = Round::new(&state_vars);
+        round.generate_constraints()?;
+        Ok(round.key_stream)
+    }
+
     pub fn consistency_check(&self, expect: Vec<u8>) {
         let output = self
             .output_vars
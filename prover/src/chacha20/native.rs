@@ -0,0 +1,78 @@
+pub const CONSTANTS: [u32; 4] = [0x61707865, 0x3320646e, 0x79622d32, 0x6b206574];
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+/// One 64-byte ChaCha20 key stream block (RFC 8439, 2.3).
+pub fn block(key: &[u8], nonce: &[u8], count: u32) -> Vec<u8> {
+    assert_eq!(key.len(), 32);
+    assert_eq!(nonce.len(), 12);
+
+    let mut state = [0u32; 16];
+    state[..4].copy_from_slice(&CONSTANTS);
+    for (i, chunk) in key.chunks(4).enumerate() {
+        state[4 + i] = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+    state[12] = count;
+    for (i, chunk) in nonce.chunks(4).enumerate() {
+        state[13 + i] = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+
+    let mut working = state;
+    for _ in 0..10 {
+        quarter_round(&mut working, 0, 4, 8, 12);
+        quarter_round(&mut working, 1, 5, 9, 13);
+        quarter_round(&mut working, 2, 6, 10, 14);
+        quarter_round(&mut working, 3, 7, 11, 15);
+        quarter_round(&mut working, 0, 5, 10, 15);
+        quarter_round(&mut working, 1, 6, 11, 12);
+        quarter_round(&mut working, 2, 7, 8, 13);
+        quarter_round(&mut working, 3, 4, 9, 14);
+    }
+
+    state
+        .iter()
+        .zip(working.iter())
+        .flat_map(|(s, w)| s.wrapping_add(*w).to_le_bytes())
+        .collect()
+}
+
+/// XOR `input` with the key stream starting at block `count`, the native
+/// counterpart of `ChaCha20Var`.
+pub fn apply_keystream(key: &[u8], nonce: &[u8], count: u32, input: &[u8]) -> Vec<u8> {
+    let mut output = vec![];
+    for (i, chunk) in input.chunks(64).enumerate() {
+        let key_stream = block(key, nonce, count.wrapping_add(i as u32));
+        output.extend(chunk.iter().zip(key_stream.iter()).map(|(m, k)| m ^ k));
+    }
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::apply_keystream;
+
+    #[test]
+    fn test_apply_keystream() {
+        let key = hex::decode("2d1dd3fe94156f0063372d1523a10b542348f3ad7491fec44390ad24a2f3edc7")
+            .unwrap();
+        let nonce = hex::decode("4a1f503da88baa6e582a2fe1").unwrap();
+        let input = hex::decode( "546f6d6f72726f772077696c6c20626520626574746572212121212121212121546f6d6f72726f772077696c6c20626520626574746572212121212121212121").unwrap();
+
+        let output = apply_keystream(&key, &nonce, 1, &input);
+        let expect = hex::decode( "8160bef4ce75a63610b85375619fe20c3bcc2e154389b74741755681dd0ad37b2201671a36852729da74b958182bafb4d9bd6c2b348ae3277aaa056e1230ef9d").unwrap();
+        assert_eq!(output, expect);
+        assert_eq!(apply_keystream(&key, &nonce, 1, &output), input);
+    }
+}
@@ -0,0 +1,421 @@
+use ark_bn254::Fr;
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    fields::{fp::FpVar, FieldVar},
+    uint8::UInt8,
+};
+use ark_relations::{
+    ns,
+    r1cs::{ConstraintSystemRef, SynthesisError},
+};
+
+use crate::{
+    mimc::{
+        bn254::{constraint::MimcBn254Var, MimcBn254},
+        MiMC,
+    },
+    poseidon::{constraint::PoseidonBn254Var, PoseidonBn254},
+    slice::{pack_slice, SliceVar},
+    template::RequestTemplate,
+    utils::{pack_byte_fp_vars, pack_bytes, pack_bytes_var},
+};
+
+/// A hash used for the public commitments, with a native half for computing
+/// public inputs and an in-circuit half.
+pub trait CommitmentGadget {
+    type Var;
+
+    fn hash(state: &[Fr]) -> Fr;
+
+    fn new_var(cs: ConstraintSystemRef<Fr>) -> Self::Var;
+
+    fn hash_var(var: &Self::Var, state: &[FpVar<Fr>]) -> Result<FpVar<Fr>, SynthesisError>;
+
+    /// The hash of `state` separated by `domain` and the byte length `len`
+    /// of the packed data; by default both are prepended to the input.
+    fn sponge(domain: Fr, len: u64, state: &[Fr]) -> Fr {
+        let mut input = vec![domain, Fr::from(len)];
+        input.extend_from_slice(state);
+        Self::hash(&input)
+    }
+
+    /// The gadget counterpart of `sponge`.
+    fn sponge_var(
+        var: &Self::Var,
+        domain: Fr,
+        len_var: &FpVar<Fr>,
+        state: &[FpVar<Fr>],
+    ) -> Result<FpVar<Fr>, SynthesisError> {
+        let mut input = vec![FpVar::Constant(domain), len_var.clone()];
+        input.extend_from_slice(state);
+        Self::hash_var(var, &input)
+    }
+}
+
+impl CommitmentGadget for MimcBn254 {
+    type Var = MimcBn254Var<Fr>;
+
+    fn hash(state: &[Fr]) -> Fr {
+        MimcBn254::permute_feistel(state, 1)[0]
+    }
+
+    fn new_var(cs: ConstraintSystemRef<Fr>) -> Self::Var {
+        let mut round_constant_vars = vec![];
+        for c in MimcBn254::ROUND_KEYS {
+            round_constant_vars.push(FpVar::new_constant(ns!(cs, "alloc round keys"), c).unwrap());
+        }
+        MimcBn254Var::new(1, &round_constant_vars, FpVar::zero())
+    }
+
+    fn hash_var(var: &Self::Var, state: &[FpVar<Fr>]) -> Result<FpVar<Fr>, SynthesisError> {
+        Ok(var.generate_constraints(state)[0].clone())
+    }
+
+    fn sponge(domain: Fr, len: u64, state: &[Fr]) -> Fr {
+        <MimcBn254 as MiMC<Fr, 220, 5>>::sponge(domain, len, state)
+    }
+
+    fn sponge_var(
+        var: &Self::Var,
+        domain: Fr,
+        len_var: &FpVar<Fr>,
+        state: &[FpVar<Fr>],
+    ) -> Result<FpVar<Fr>, SynthesisError> {
+        Ok(var.sponge(domain, len_var, state))
+    }
+}
+
+impl CommitmentGadget for PoseidonBn254 {
+    type Var = PoseidonBn254Var;
+
+    fn hash(state: &[Fr]) -> Fr {
+        PoseidonBn254::hash(state)
+    }
+
+    fn new_var(cs: ConstraintSystemRef<Fr>) -> Self::Var {
+        PoseidonBn254Var::new(cs)
+    }
+
+    fn hash_var(var: &Self::Var, state: &[FpVar<Fr>]) -> Result<FpVar<Fr>, SynthesisError> {
+        var.generate_constraints(state)
+    }
+}
+
+/// Domain tag of the prompt commitment.
+pub const PROMPT_DOMAIN: u64 = 1;
+/// Domain tag of the cipher commitment.
+pub const CIPHER_DOMAIN: u64 = 2;
+/// Domain tag of the message commitments.
+pub const MESSAGE_DOMAIN: u64 = 3;
+/// Domain tag of the tools commitment.
+pub const TOOLS_DOMAIN: u64 = 4;
+/// Domain tag of the output commitment.
+pub const OUTPUT_DOMAIN: u64 = 5;
+/// Domain tag of the traffic and handshake secret commitments.
+pub const SECRET_DOMAIN: u64 = 6;
+/// Domain tag of the API key commitment.
+pub const API_KEY_DOMAIN: u64 = 7;
+
+/// Upper bound of the role of a message, such as `assistant`.
+pub const MAX_ROLE_LEN: usize = 16;
+
+/// The hash of `bytes` under the canonical packing of `utils::pack_bytes`.
+pub fn commit<C: CommitmentGadget>(bytes: &[u8]) -> Fr {
+    C::hash(&pack_bytes(bytes))
+}
+
+/// The length-prefixed sponge of `bytes` zero-padded to `max_len` bytes and
+/// followed by `salt`.
+fn commit_padded<C: CommitmentGadget>(domain: u64, bytes: &[u8], max_len: usize, salt: Fr) -> Fr {
+    assert!(bytes.len() <= max_len);
+
+    let mut padded = bytes.to_vec();
+    padded.resize(max_len, 0);
+    let mut packed = pack_bytes(&padded);
+    packed.push(salt);
+    C::sponge(Fr::from(domain), bytes.len() as u64, &packed)
+}
+
+/// The `prompt_commitment` public input of `ZkPrompt`, the length-prefixed
+/// sponge of the prompt zero-padded to `max_len` bytes and followed by
+/// `salt`. Auditors open it with the prompt and the salt.
+pub fn commit_prompt<C: CommitmentGadget>(prompt: &[u8], max_len: usize, salt: Fr) -> Fr {
+    commit_padded::<C>(PROMPT_DOMAIN, prompt, max_len, salt)
+}
+
+/// Open the `prompt_commitment` of a request to `template` with the prompt
+/// and the salt kept by the prover, for an auditor.
+pub fn open_commitment<C: CommitmentGadget>(
+    prompt: &[u8],
+    template: &RequestTemplate,
+    salt: Fr,
+) -> Fr {
+    commit_prompt::<C>(prompt, template.max_prompt_len, salt)
+}
+
+/// The `output_commitment` public input of `ZkResponse`, committed as the
+/// prompt is under its own domain.
+pub fn commit_output<C: CommitmentGadget>(output: &[u8], max_len: usize, salt: Fr) -> Fr {
+    commit_padded::<C>(OUTPUT_DOMAIN, output, max_len, salt)
+}
+
+/// One of the `message_commitments` public inputs, the length-prefixed
+/// sponge of the role and the content of a message, each as in
+/// `slice::pack_slice`, followed by `salt`.
+pub fn commit_message<C: CommitmentGadget>(
+    role: &[u8],
+    content: &[u8],
+    max_len: usize,
+    salt: Fr,
+) -> Fr {
+    let mut packed = pack_slice(role, MAX_ROLE_LEN);
+    packed.extend(pack_slice::<Fr>(content, max_len));
+    packed.push(salt);
+    C::sponge(
+        Fr::from(MESSAGE_DOMAIN),
+        (role.len() + content.len()) as u64,
+        &packed,
+    )
+}
+
+/// The `tools_commitment` public input, the length-prefixed sponge of the
+/// tool definitions, each as in `slice::pack_slice`, followed by `salt`.
+pub fn commit_tools<C: CommitmentGadget>(tools: &[&[u8]], max_len: usize, salt: Fr) -> Fr {
+    let mut packed = vec![];
+    for tool in tools {
+        packed.extend(pack_slice::<Fr>(tool, max_len));
+    }
+    packed.push(salt);
+    C::sponge(
+        Fr::from(TOOLS_DOMAIN),
+        tools.iter().map(|tool| tool.len() as u64).sum(),
+        &packed,
+    )
+}
+
+/// The length-prefixed sponge of `bytes`, whose length is public.
+fn commit_bytes<C: CommitmentGadget>(domain: u64, bytes: &[u8]) -> Fr {
+    C::sponge(Fr::from(domain), bytes.len() as u64, &pack_bytes(bytes))
+}
+
+/// The `cipher_commitment` public input, over the records as sent on the
+/// wire, each cipher texts followed by its tag.
+pub fn commit_cipher<C: CommitmentGadget>(record: &[u8]) -> Fr {
+    commit_bytes::<C>(CIPHER_DOMAIN, record)
+}
+
+/// The `secret_commitment` public input, over a traffic secret, or over
+/// the handshake secret and the transcript hash of an exchange.
+pub fn commit_secret<C: CommitmentGadget>(secret: &[u8]) -> Fr {
+    commit_bytes::<C>(SECRET_DOMAIN, secret)
+}
+
+/// The public input binding a request to `api_key` when the template sets
+/// `bind_api_key`.
+pub fn commit_api_key<C: CommitmentGadget>(api_key: &[u8]) -> Fr {
+    commit_bytes::<C>(API_KEY_DOMAIN, api_key)
+}
+
+/// The gadget counterpart of `commit`.
+pub fn commit_var<C: CommitmentGadget>(
+    hash_var: &C::Var,
+    vars: &[UInt8<Fr>],
+) -> Result<FpVar<Fr>, SynthesisError> {
+    C::hash_var(hash_var, &pack_bytes_var(vars)?)
+}
+
+/// The gadget counterpart of `commit_padded`, over a slice of private
+/// length.
+fn commit_padded_var<C: CommitmentGadget>(
+    hash_var: &C::Var,
+    domain: u64,
+    slice: &SliceVar<Fr>,
+    salt_var: &FpVar<Fr>,
+) -> Result<FpVar<Fr>, SynthesisError> {
+    let mut packed_vars = pack_byte_fp_vars(&slice.byte_vars);
+    packed_vars.push(salt_var.clone());
+    C::sponge_var(hash_var, Fr::from(domain), &slice.len_var, &packed_vars)
+}
+
+/// The gadget counterpart of `commit_prompt`, over a prompt of private
+/// length.
+pub fn commit_prompt_var<C: CommitmentGadget>(
+    hash_var: &C::Var,
+    prompt: &SliceVar<Fr>,
+    salt_var: &FpVar<Fr>,
+) -> Result<FpVar<Fr>, SynthesisError> {
+    commit_padded_var::<C>(hash_var, PROMPT_DOMAIN, prompt, salt_var)
+}
+
+/// The gadget counterpart of `commit_output`.
+pub fn commit_output_var<C: CommitmentGadget>(
+    hash_var: &C::Var,
+    output: &SliceVar<Fr>,
+    salt_var: &FpVar<Fr>,
+) -> Result<FpVar<Fr>, SynthesisError> {
+    commit_padded_var::<C>(hash_var, OUTPUT_DOMAIN, output, salt_var)
+}
+
+/// The gadget counterpart of `commit_message`.
+pub fn commit_message_var<C: CommitmentGadget>(
+    hash_var: &C::Var,
+    role: &SliceVar<Fr>,
+    content: &SliceVar<Fr>,
+    salt_var: &FpVar<Fr>,
+) -> Result<FpVar<Fr>, SynthesisError> {
+    let mut packed_vars = role.pack();
+    packed_vars.extend(content.pack());
+    packed_vars.push(salt_var.clone());
+    C::sponge_var(
+        hash_var,
+        Fr::from(MESSAGE_DOMAIN),
+        &(&role.len_var + &content.len_var),
+        &packed_vars,
+    )
+}
+
+/// The gadget counterpart of `commit_tools`.
+pub fn commit_tools_var<C: CommitmentGadget>(
+    hash_var: &C::Var,
+    tools: &[SliceVar<Fr>],
+    salt_var: &FpVar<Fr>,
+) -> Result<FpVar<Fr>, SynthesisError> {
+    let mut packed_vars = vec![];
+    for tool in tools {
+        packed_vars.extend(tool.pack());
+    }
+    packed_vars.push(salt_var.clone());
+    let len_var = tools
+        .iter()
+        .fold(FpVar::zero(), |sum, tool| sum + &tool.len_var);
+    C::sponge_var(hash_var, Fr::from(TOOLS_DOMAIN), &len_var, &packed_vars)
+}
+
+/// The gadget counterpart of `commit_bytes`.
+fn commit_bytes_var<C: CommitmentGadget>(
+    hash_var: &C::Var,
+    domain: u64,
+    vars: &[UInt8<Fr>],
+) -> Result<FpVar<Fr>, SynthesisError> {
+    let len_var = FpVar::Constant(Fr::from(vars.len() as u64));
+    C::sponge_var(hash_var, Fr::from(domain), &len_var, &pack_bytes_var(vars)?)
+}
+
+/// The gadget counterpart of `commit_cipher`.
+pub fn commit_cipher_var<C: CommitmentGadget>(
+    hash_var: &C::Var,
+    vars: &[UInt8<Fr>],
+) -> Result<FpVar<Fr>, SynthesisError> {
+    commit_bytes_var::<C>(hash_var, CIPHER_DOMAIN, vars)
+}
+
+/// The gadget counterpart of `commit_secret`.
+pub fn commit_secret_var<C: CommitmentGadget>(
+    hash_var: &C::Var,
+    vars: &[UInt8<Fr>],
+) -> Result<FpVar<Fr>, SynthesisError> {
+    commit_bytes_var::<C>(hash_var, SECRET_DOMAIN, vars)
+}
+
+/// The gadget counterpart of `commit_api_key`, over a key located in the
+/// head.
+pub fn commit_api_key_var<C: CommitmentGadget>(
+    hash_var: &C::Var,
+    api_key: &SliceVar<Fr>,
+) -> Result<FpVar<Fr>, SynthesisError> {
+    C::sponge_var(
+        hash_var,
+        Fr::from(API_KEY_DOMAIN),
+        &api_key.len_var,
+        &pack_byte_fp_vars(&api_key.byte_vars),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar, uint8::UInt8, R1CSVar};
+    use ark_relations::{ns, r1cs::ConstraintSystem};
+
+    use super::{
+        commit, commit_api_key, commit_cipher, commit_cipher_var, commit_output, commit_output_var,
+        commit_prompt, commit_prompt_var, commit_secret, commit_secret_var, commit_var,
+        CommitmentGadget,
+    };
+    use crate::{mimc::bn254::MimcBn254, poseidon::PoseidonBn254, slice::SliceVar};
+
+    fn commit_constraints<C: CommitmentGadget>(data: &[u8]) -> usize {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let data_vars = data
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc data"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+        let hash_var = C::new_var(cs.clone());
+        let commitment = commit_var::<C>(&hash_var, &data_vars).unwrap();
+        assert_eq!(commitment.value().unwrap(), commit::<C>(data));
+        assert!(cs.is_satisfied().unwrap());
+        cs.num_constraints()
+    }
+
+    #[test]
+    fn test_commitment_gadgets() {
+        let data = (0..1000).map(|i| i as u8).collect::<Vec<_>>();
+        let mimc = commit_constraints::<MimcBn254>(&data);
+        let poseidon = commit_constraints::<PoseidonBn254>(&data);
+        assert!(poseidon < mimc);
+    }
+
+    fn check_domain_separation<C: CommitmentGadget>() {
+        let salt = Fr::from(42u8);
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let data = b"\"you are a zypher girl!\"";
+        let data_vars = data
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc data"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+        let start_var = FpVar::new_witness(ns!(cs, "alloc start"), || Ok(Fr::from(1u8))).unwrap();
+        let len_var = FpVar::new_witness(ns!(cs, "alloc len"), || Ok(Fr::from(22u8))).unwrap();
+        let prompt = SliceVar::new(&data_vars, &start_var, &len_var, 40, 1, 1).unwrap();
+        let salt_var = FpVar::new_witness(ns!(cs, "alloc salt"), || Ok(salt)).unwrap();
+        let hash_var = C::new_var(cs.clone());
+        let prompt_var = commit_prompt_var::<C>(&hash_var, &prompt, &salt_var).unwrap();
+        let output_var = commit_output_var::<C>(&hash_var, &prompt, &salt_var).unwrap();
+        let cipher_var = commit_cipher_var::<C>(&hash_var, &data_vars).unwrap();
+        let secret_var = commit_secret_var::<C>(&hash_var, &data_vars).unwrap();
+        assert_eq!(
+            prompt_var.value().unwrap(),
+            commit_prompt::<C>(&data[1..23], 40, salt)
+        );
+        assert_eq!(
+            output_var.value().unwrap(),
+            commit_output::<C>(&data[1..23], 40, salt)
+        );
+        assert_ne!(prompt_var.value().unwrap(), output_var.value().unwrap());
+        assert_eq!(cipher_var.value().unwrap(), commit_cipher::<C>(data));
+        assert_eq!(secret_var.value().unwrap(), commit_secret::<C>(data));
+        assert!(cs.is_satisfied().unwrap());
+
+        // trailing zero bytes pack to the same elements
+        let mut padded = data.to_vec();
+        padded.push(0);
+        assert_eq!(commit::<C>(data), commit::<C>(&padded));
+        assert_ne!(
+            commit_prompt::<C>(data, 40, salt),
+            commit_prompt::<C>(&padded, 40, salt)
+        );
+        assert_ne!(commit_cipher::<C>(data), commit_cipher::<C>(&padded));
+        assert_ne!(
+            commit_prompt::<C>(data, data.len(), Fr::from(0u8)),
+            commit_cipher::<C>(data)
+        );
+        // nor a secret passes for a cipher text or an API key
+        assert_ne!(commit_secret::<C>(data), commit_cipher::<C>(data));
+        assert_ne!(commit_secret::<C>(data), commit_api_key::<C>(data));
+    }
+
+    #[test]
+    fn test_domain_separation() {
+        check_domain_separation::<MimcBn254>();
+        check_domain_separation::<PoseidonBn254>();
+    }
+}
@@ -0,0 +1,274 @@
+use ark_bn254::Fr;
+use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, fields::fp::FpVar, uint8::UInt8, R1CSVar};
+use ark_relations::{
+    ns,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+
+use crate::{
+    build_cs::ZkPrompt,
+    commitment::{commit_secret, commit_secret_var, CommitmentGadget},
+    hkdf::{application_traffic_secrets, native},
+    mimc::bn254::MimcBn254,
+    response::ZkResponse,
+    suite::CipherSuite,
+    template::{RequestTemplate, ResponseTemplate},
+};
+
+/// The public inputs of `ZkExchange`, in allocation order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExchangePublicInputs {
+    pub request_seq: u64,
+    pub response_seq: u64,
+    pub prompt_len_min: u64,
+    pub prompt_len_max: u64,
+    pub prompt_commitment: Fr,
+    pub output_commitment: Fr,
+    pub request_cipher_commitment: Fr,
+    pub response_cipher_commitment: Fr,
+    /// Over the handshake secret followed by the transcript hash.
+    pub handshake_commitment: Fr,
+    /// Present iff the request template sets `bind_api_key`.
+    pub api_key_commitment: Option<Fr>,
+    pub message_commitments: Vec<Fr>,
+    /// Present iff the request template sets `tools`.
+    pub tools_commitment: Option<Fr>,
+    /// The values of `disclose` in the request template.
+    pub disclosed: Vec<Vec<Fr>>,
+    /// The values of `numbers` in the request template, in fixed point.
+    pub numbers: Vec<u64>,
+}
+
+impl ExchangePublicInputs {
+    pub fn to_vec(&self) -> Vec<Fr> {
+        vec![
+            Fr::from(self.request_seq),
+            Fr::from(self.response_seq),
+            Fr::from(self.prompt_len_min),
+            Fr::from(self.prompt_len_max),
+            self.prompt_commitment,
+            self.output_commitment,
+            self.request_cipher_commitment,
+            self.response_cipher_commitment,
+            self.handshake_commitment,
+        ]
+        .into_iter()
+        .chain(self.api_key_commitment)
+        .chain(self.message_commitments.iter().copied())
+        .chain(self.tools_commitment)
+        .chain(self.disclosed.iter().flatten().copied())
+        .chain(self.numbers.iter().map(|n| Fr::from(*n)))
+        .collect()
+    }
+}
+
+/// Proves the records of a request and of a response of the same session:
+/// both traffic secrets are derived in circuit from the handshake secret and
+/// the transcript hash, committed together.
+pub struct ZkExchange<C: CommitmentGadget = MimcBn254> {
+    pub prompt: ZkPrompt<C>,
+    pub response: ZkResponse<C>,
+    pub handshake_secret: Vec<u8>,
+    /// Hash of the transcript up to the server Finished.
+    pub transcript_hash: Vec<u8>,
+}
+
+impl<C: CommitmentGadget> ZkExchange<C> {
+    /// Combine the two halves, replacing their traffic secrets with the ones
+    /// derived from `handshake_secret`.
+    pub fn new(
+        mut prompt: ZkPrompt<C>,
+        mut response: ZkResponse<C>,
+        handshake_secret: &[u8],
+        transcript_hash: &[u8],
+    ) -> Self {
+        assert_eq!(prompt.suite, response.suite);
+
+        let (client, server) =
+            native::application_traffic_secrets(handshake_secret, transcript_hash);
+        prompt.secret = client;
+        response.secret = server;
+
+        Self {
+            prompt,
+            response,
+            handshake_secret: handshake_secret.to_vec(),
+            transcript_hash: transcript_hash.to_vec(),
+        }
+    }
+
+    /// A circuit with an all zero witness, used for key generation.
+    pub fn empty(
+        request_template: &RequestTemplate,
+        response_template: &ResponseTemplate,
+        suite: CipherSuite,
+        request_lens: &[usize],
+        response_lens: &[usize],
+    ) -> Self {
+        Self::new(
+            ZkPrompt::empty(request_template, suite, request_lens),
+            ZkResponse::empty(response_template, suite, response_lens),
+            &[0; 32],
+            &[0; 32],
+        )
+    }
+
+    /// Compute the public inputs natively, as `generate_constraints` does.
+    pub fn public_inputs(&self) -> Result<ExchangePublicInputs, SynthesisError> {
+        let prompt = self.prompt.public_inputs()?;
+        let response = self.response.public_inputs()?;
+
+        Ok(ExchangePublicInputs {
+            request_seq: prompt.seq,
+            response_seq: response.seq,
+            prompt_len_min: prompt.prompt_len_min,
+            prompt_len_max: prompt.prompt_len_max,
+            prompt_commitment: prompt.prompt_commitment,
+            output_commitment: response.output_commitment,
+            request_cipher_commitment: prompt.cipher_commitment,
+            response_cipher_commitment: response.cipher_commitment,
+            handshake_commitment: commit_secret::<C>(
+                &[self.handshake_secret.as_slice(), &self.transcript_hash].concat(),
+            ),
+            api_key_commitment: prompt.api_key_commitment,
+            message_commitments: prompt.message_commitments,
+            tools_commitment: prompt.tools_commitment,
+            disclosed: prompt.disclosed,
+            numbers: prompt.numbers,
+        })
+    }
+}
+
+impl<C: CommitmentGadget> ConstraintSynthesizer<Fr> for ZkExchange<C> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let (prompt, response) = (self.prompt, self.response);
+
+        let request_seq_var =
+            FpVar::new_input(ns!(cs, "public request seq"), || Ok(Fr::from(prompt.seq)))?;
+        let response_seq_var = FpVar::new_input(ns!(cs, "public response seq"), || {
+            Ok(Fr::from(response.seq))
+        })?;
+
+        let handshake_secret_vars = self
+            .handshake_secret
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc handshake secret"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+        let transcript_hash_vars = self
+            .transcript_hash
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc transcript hash"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+        let (client_secret_vars, server_secret_vars) =
+            application_traffic_secrets(&handshake_secret_vars, &transcript_hash_vars)?;
+
+        let hash_var = C::new_var(cs.clone());
+        let request =
+            prompt.request_vars(cs.clone(), &hash_var, &client_secret_vars, &request_seq_var)?;
+        let (output_commitment, response_cipher_commitment) = response.commitment_vars(
+            cs.clone(),
+            &hash_var,
+            &server_secret_vars,
+            &response_seq_var,
+        )?;
+
+        // in the order of `ExchangePublicInputs`
+        let commitments = [
+            request.prompt_commitment,
+            output_commitment,
+            request.cipher_commitment,
+            response_cipher_commitment,
+            commit_secret_var::<C>(
+                &hash_var,
+                &[handshake_secret_vars.as_slice(), &transcript_hash_vars].concat(),
+            )?,
+        ];
+        for commitment in commitments.into_iter().chain(request.disclosure_vars) {
+            let pi_commitment =
+                FpVar::new_input(ns!(cs, "public commitment"), || commitment.value())?;
+            pi_commitment.enforce_equal(&commitment)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Fr;
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+
+    use super::ZkExchange;
+    use crate::{
+        build_cs::test::sample_prompt,
+        record::{decrypt_records, encrypt_records, MAX_RECORD_LEN},
+        response::test::sample_response,
+        suite::CipherSuite,
+    };
+
+    fn sample_exchange() -> ZkExchange {
+        // RFC 8448, simple 1-RTT handshake
+        let handshake_secret =
+            hex::decode("1dc826e93606aa6fdc0aadc12f741b01046aa6b99f691ed221a9f0ca043fbeac")
+                .unwrap();
+        let transcript_hash =
+            hex::decode("9608102a0f1ccc6db6250b7b7e417b1a000eaada3aaae4777a7686c9ff83df13")
+                .unwrap();
+
+        let prompt = sample_prompt(CipherSuite::Chacha20Poly1305Sha256);
+        let response = sample_response(CipherSuite::Chacha20Poly1305Sha256);
+        let request_texts =
+            decrypt_records(prompt.suite, &prompt.secret, prompt.seq, &prompt.records);
+        let response_texts = decrypt_records(
+            response.suite,
+            &response.secret,
+            response.seq,
+            &response.records,
+        );
+
+        // re-encrypt both under the secrets of the session
+        let mut exchange = ZkExchange::new(prompt, response, &handshake_secret, &transcript_hash);
+        exchange.prompt.records = encrypt_records(
+            exchange.prompt.suite,
+            &exchange.prompt.secret,
+            exchange.prompt.seq,
+            &request_texts,
+            MAX_RECORD_LEN,
+            exchange.prompt.template.record_padding,
+        );
+        exchange.response.records = encrypt_records(
+            exchange.response.suite,
+            &exchange.response.secret,
+            exchange.response.seq,
+            &response_texts,
+            MAX_RECORD_LEN,
+            exchange.response.template.record_padding,
+        );
+        exchange
+    }
+
+    #[test]
+    fn test_zk_exchange() {
+        let exchange = sample_exchange();
+        let public_inputs = exchange.public_inputs().unwrap();
+        assert_eq!(
+            public_inputs.prompt_commitment,
+            exchange.prompt.public_inputs().unwrap().prompt_commitment
+        );
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        exchange.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(
+            cs.borrow().unwrap().instance_assignment[1..],
+            public_inputs.to_vec()
+        );
+
+        // a response from another session
+        let mut exchange = sample_exchange();
+        exchange.transcript_hash[0] ^= 1;
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        exchange.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+}
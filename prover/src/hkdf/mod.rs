@@ -0,0 +1,154 @@
+use ark_ff::PrimeField;
+use ark_r1cs_std::{uint32::UInt32, uint8::UInt8};
+use ark_relations::r1cs::SynthesisError;
+use native::label_info;
+
+use crate::sha256::{compress, finalize, initial_state, native::hash};
+
+pub mod native;
+
+/// HMAC-SHA256 keyed by a TLS secret, with the padded key blocks compressed
+/// once and shared by every expansion.
+pub struct HkdfVar<F: PrimeField> {
+    pub inner_state_vars: Vec<UInt32<F>>,
+    pub outer_state_vars: Vec<UInt32<F>>,
+}
+
+impl<F: PrimeField> HkdfVar<F> {
+    pub fn new(secret_vars: &[UInt8<F>]) -> Result<Self, SynthesisError> {
+        assert!(secret_vars.len() <= 64);
+
+        let key_block = |pad: u8| {
+            let mut block_vars = secret_vars
+                .iter()
+                .map(|x| x ^ UInt8::constant(pad))
+                .collect::<Vec<_>>();
+            block_vars.resize(64, UInt8::constant(pad));
+            block_vars
+        };
+
+        Ok(Self {
+            inner_state_vars: compress(&initial_state(), &key_block(0x36))?,
+            outer_state_vars: compress(&initial_state(), &key_block(0x5c))?,
+        })
+    }
+
+    pub fn hmac(&self, msg_vars: &[UInt8<F>]) -> Result<Vec<UInt8<F>>, SynthesisError> {
+        let inner_vars = finalize(&self.inner_state_vars, msg_vars, 64)?;
+        finalize(&self.outer_state_vars, &inner_vars, 64)
+    }
+
+    /// The gadget counterpart of `native::expand_label`, with a public label.
+    pub fn expand_label(
+        &self,
+        label: &[u8],
+        context_vars: &[UInt8<F>],
+        len: usize,
+    ) -> Result<Vec<UInt8<F>>, SynthesisError> {
+        assert!(len <= 32);
+
+        // the context bytes are placeholders, replaced by `context_vars`
+        let info = label_info(label, &vec![0; context_vars.len()], len);
+        let prefix_len = info.len() - context_vars.len();
+        let mut info_vars = info[..prefix_len]
+            .iter()
+            .map(|x| UInt8::constant(*x))
+            .collect::<Vec<_>>();
+        info_vars.extend_from_slice(context_vars);
+        info_vars.push(UInt8::constant(1));
+        let mut okm_vars = self.hmac(&info_vars)?;
+        okm_vars.truncate(len);
+        Ok(okm_vars)
+    }
+
+    pub fn traffic_key(&self, key_len: usize) -> Result<Vec<UInt8<F>>, SynthesisError> {
+        self.expand_label(b"key", &[], key_len)
+    }
+
+    pub fn traffic_iv(&self) -> Result<Vec<UInt8<F>>, SynthesisError> {
+        self.expand_label(b"iv", &[], 12)
+    }
+}
+
+/// The client and server traffic secret variables.
+pub type TrafficSecrets<F> = (Vec<UInt8<F>>, Vec<UInt8<F>>);
+
+/// The gadget counterpart of `native::application_traffic_secrets`.
+pub fn application_traffic_secrets<F: PrimeField>(
+    handshake_secret_vars: &[UInt8<F>],
+    transcript_hash_vars: &[UInt8<F>],
+) -> Result<TrafficSecrets<F>, SynthesisError> {
+    let empty_hash_vars = hash(&[])
+        .into_iter()
+        .map(UInt8::constant)
+        .collect::<Vec<_>>();
+    let derived_vars =
+        HkdfVar::new(handshake_secret_vars)?.expand_label(b"derived", &empty_hash_vars, 32)?;
+    let master_secret_vars = HkdfVar::new(&derived_vars)?.hmac(&vec![UInt8::constant(0); 32])?;
+
+    let master = HkdfVar::new(&master_secret_vars)?;
+    Ok((
+        master.expand_label(b"c ap traffic", transcript_hash_vars, 32)?,
+        master.expand_label(b"s ap traffic", transcript_hash_vars, 32)?,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, uint8::UInt8, R1CSVar};
+    use ark_relations::{ns, r1cs::ConstraintSystem};
+
+    use super::{application_traffic_secrets, native, HkdfVar};
+
+    #[test]
+    fn test_hkdf_constraint() {
+        let secret =
+            hex::decode("ac49f185f79856dad7a170ce8acc3f297229f6392ce3b107e30a560b23922cca")
+                .unwrap();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let secret_vars = secret
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc secret"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+        let hkdf = HkdfVar::new(&secret_vars).unwrap();
+
+        let key_vars = hkdf.traffic_key(32).unwrap();
+        assert_eq!(
+            key_vars.value().unwrap(),
+            native::derive_traffic_key(&secret, 32)
+        );
+        let iv_vars = hkdf.traffic_iv().unwrap();
+        assert_eq!(iv_vars.value().unwrap(), native::derive_traffic_iv(&secret));
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_application_traffic_secrets_constraint() {
+        let handshake_secret =
+            hex::decode("1dc826e93606aa6fdc0aadc12f741b01046aa6b99f691ed221a9f0ca043fbeac")
+                .unwrap();
+        let transcript_hash =
+            hex::decode("9608102a0f1ccc6db6250b7b7e417b1a000eaada3aaae4777a7686c9ff83df13")
+                .unwrap();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let handshake_secret_vars = handshake_secret
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc secret"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+        let transcript_hash_vars = transcript_hash
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc hash"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+
+        let (client_vars, server_vars) =
+            application_traffic_secrets(&handshake_secret_vars, &transcript_hash_vars).unwrap();
+        let (client, server) =
+            native::application_traffic_secrets(&handshake_secret, &transcript_hash);
+        assert_eq!(client_vars.value().unwrap(), client);
+        assert_eq!(server_vars.value().unwrap(), server);
+        assert!(cs.is_satisfied().unwrap());
+    }
+}
@@ -0,0 +1,145 @@
+use crate::sha256::native::{compress, finalize, hash, H0};
+
+const BLOCK_LEN: usize = 64;
+
+/// The key block of HMAC-SHA256 xor-ed with `pad`.
+pub fn key_block(key: &[u8], pad: u8) -> Vec<u8> {
+    let mut block = match key.len() > BLOCK_LEN {
+        true => hash(key),
+        false => key.to_vec(),
+    };
+    block.resize(BLOCK_LEN, 0);
+    block.iter().map(|x| x ^ pad).collect()
+}
+
+pub fn hmac(key: &[u8], msg: &[u8]) -> Vec<u8> {
+    let mut inner = H0;
+    compress(&mut inner, &key_block(key, 0x36));
+    let inner = finalize(inner, msg, BLOCK_LEN);
+
+    let mut outer = H0;
+    compress(&mut outer, &key_block(key, 0x5c));
+    finalize(outer, &inner, BLOCK_LEN)
+}
+
+/// The `HkdfLabel` structure of RFC 8446, 7.1.
+pub fn label_info(label: &[u8], context: &[u8], len: usize) -> Vec<u8> {
+    let mut info = (len as u16).to_be_bytes().to_vec();
+    info.push((6 + label.len()) as u8);
+    info.extend_from_slice(b"tls13 ");
+    info.extend_from_slice(label);
+    info.push(context.len() as u8);
+    info.extend_from_slice(context);
+    info
+}
+
+/// HKDF-Expand-Label for outputs of at most one hash length.
+pub fn expand_label(secret: &[u8], label: &[u8], context: &[u8], len: usize) -> Vec<u8> {
+    assert!(len <= 32);
+
+    let mut info = label_info(label, context, len);
+    info.push(1);
+    let mut okm = hmac(secret, &info);
+    okm.truncate(len);
+    okm
+}
+
+pub fn extract(salt: &[u8], ikm: &[u8]) -> Vec<u8> {
+    hmac(salt, ikm)
+}
+
+/// The master secret of the key schedule (RFC 8446, 7.1), with no PSK.
+pub fn master_secret(handshake_secret: &[u8]) -> Vec<u8> {
+    let derived = expand_label(handshake_secret, b"derived", &hash(&[]), 32);
+    extract(&derived, &[0; 32])
+}
+
+/// The client and server application traffic secrets of the session with
+/// `handshake_secret`, given the hash of the transcript up to the server
+/// Finished.
+pub fn application_traffic_secrets(
+    handshake_secret: &[u8],
+    transcript_hash: &[u8],
+) -> (Vec<u8>, Vec<u8>) {
+    let master_secret = master_secret(handshake_secret);
+    (
+        expand_label(&master_secret, b"c ap traffic", transcript_hash, 32),
+        expand_label(&master_secret, b"s ap traffic", transcript_hash, 32),
+    )
+}
+
+pub fn derive_traffic_key(secret: &[u8], key_len: usize) -> Vec<u8> {
+    expand_label(secret, b"key", &[], key_len)
+}
+
+pub fn derive_traffic_iv(secret: &[u8]) -> Vec<u8> {
+    expand_label(secret, b"iv", &[], 12)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        application_traffic_secrets, derive_traffic_iv, derive_traffic_key, hmac, master_secret,
+    };
+
+    #[test]
+    fn test_hmac() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            hex::encode(hmac(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_master_secret() {
+        // RFC 8448, simple 1-RTT handshake
+        let handshake_secret =
+            hex::decode("1dc826e93606aa6fdc0aadc12f741b01046aa6b99f691ed221a9f0ca043fbeac")
+                .unwrap();
+        assert_eq!(
+            hex::encode(master_secret(&handshake_secret)),
+            "18df06843d13a08bf2a449844c5f8a478001bc4d4c627984d5a41da8d0402919"
+        );
+
+        let transcript_hash =
+            hex::decode("9608102a0f1ccc6db6250b7b7e417b1a000eaada3aaae4777a7686c9ff83df13")
+                .unwrap();
+        let (client, server) = application_traffic_secrets(&handshake_secret, &transcript_hash);
+        assert_eq!(
+            hex::encode(client),
+            "fc0ca287a126bedc24661dcb6111763c6fdbe178c55d76f9c258a0091a505af6"
+        );
+        assert_eq!(
+            hex::encode(server),
+            "0fc2e8f76c841474766adf89625c67d4f59100689c5dc221b11cbfa77529d527"
+        );
+    }
+
+    #[test]
+    fn test_derive_traffic_key() {
+        let secret =
+            hex::decode("ac49f185f79856dad7a170ce8acc3f297229f6392ce3b107e30a560b23922cca")
+                .unwrap();
+        assert_eq!(
+            hex::encode(derive_traffic_key(&secret, 32)),
+            "2d1dd3fe94156f0063372d1523a10b542348f3ad7491fec44390ad24a2f3edc7"
+        );
+        assert_eq!(
+            hex::encode(derive_traffic_iv(&secret)),
+            "4a1f503da88baa6e582a2fe1"
+        );
+
+        let secret =
+            hex::decode("4164bcc581ef89891256d9a7752205fb1c0504920eaa027acb5b9085033933a7")
+                .unwrap();
+        assert_eq!(
+            hex::encode(derive_traffic_key(&secret, 16)),
+            "9bc2a070ad6a05ca5c9b72b2693bbfb7"
+        );
+        assert_eq!(
+            hex::encode(derive_traffic_iv(&secret)),
+            "05050354fb7523f9ba9693b0"
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    convert::ToBitsGadget,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    prelude::Boolean,
+    uint8::UInt8,
+};
+use ark_relations::r1cs::SynthesisError;
+
+use crate::{
+    slice::{mask_position, SliceVar},
+    utils::{any, bytes_to_fp_var, enforce_bits},
+};
+
+pub mod native;
+
+pub const CONTENT_TYPE: &str = "content-type";
+pub const CONTENT_LENGTH: &str = "content-length";
+const MAX_CONTENT_LENGTH_DIGITS: usize = 8;
+
+/// A streaming parser over the header lines of an HTTP/1.1 message, which
+/// locates the values of the headers `names` whatever their order and case
+/// and whatever other headers are sent.
+///
+/// Each byte moves one of the states of a line: the start of a line, its
+/// name, the blanks after the colon, its value and the `\r` ending it, until
+/// the blank line. Names are folded to lowercase by setting bit 5, which is
+/// exact for the letters, digits and dashes of `names`, and are kept as a
+/// big-endian integer. A bare `\n`, a blank in a name or a line without a
+/// colon is rejected; the bytes of a name are not validated further.
+pub struct HeadVar<F: PrimeField> {
+    pub data_vars: Vec<UInt8<F>>,
+    pub names: Vec<String>,
+    /// Where the header lines start, right after the request or status
+    /// line.
+    pub start: usize,
+    /// The position of the body, right after the blank line.
+    pub body_start_var: FpVar<F>,
+    /// For each name, whether each byte is part of its value.
+    mask_vars: Vec<Vec<Boolean<F>>>,
+}
+
+impl<F: PrimeField> HeadVar<F> {
+    pub fn new(
+        data_vars: &[UInt8<F>],
+        start: usize,
+        names: &[&str],
+    ) -> Result<Self, SynthesisError> {
+        let byte_vars = bytes_to_fp_var(data_vars)?;
+        let name_ints = names
+            .iter()
+            .map(|name| {
+                assert!(name.len() < 32, "name too long");
+                assert!(
+                    name.bytes()
+                        .all(|b| b == b'-' || b.is_ascii_lowercase() || b.is_ascii_digit()),
+                    "name not folded"
+                );
+                (
+                    FpVar::Constant(F::from_be_bytes_mod_order(name.as_bytes())),
+                    FpVar::Constant(F::from(name.len() as u64)),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut line_start = Boolean::TRUE;
+        let mut in_name = Boolean::FALSE;
+        let mut in_blank = Boolean::FALSE;
+        let mut in_value = Boolean::FALSE;
+        let mut line_cr = Boolean::FALSE;
+        let mut blank_cr = Boolean::FALSE;
+        let mut done = Boolean::FALSE;
+        let mut name = FpVar::zero();
+        let mut name_len = FpVar::zero();
+        let mut body_start_var = FpVar::Constant(F::from(start as u64));
+        let mut mask_vars = vec![vec![Boolean::FALSE; start]; names.len()];
+
+        for (b, var) in byte_vars[start..].iter().zip(&data_vars[start..]) {
+            let is = |c: u8| b.is_eq(&FpVar::Constant(F::from(c)));
+            let colon = is(b':')?;
+            let cr = is(b'\r')?;
+            let lf = is(b'\n')?;
+            let blank = is(b' ')? | is(b'\t')?;
+            let folded = b + FpVar::from(!&var.to_bits_le()?[5]) * F::from(0x20u8);
+
+            let invalid = [
+                &line_start & (&colon | &blank | &lf),
+                &in_name & (&blank | &cr | &lf),
+                (&in_blank | &in_value) & &lf,
+                (&line_cr | &blank_cr) & !&lf,
+            ];
+            any(&invalid)?.enforce_equal(&Boolean::FALSE)?;
+
+            let value_byte = ((&in_blank & !&blank) | &in_value) & !&cr;
+            for ((name_int, len), mask) in name_ints.iter().zip(mask_vars.iter_mut()) {
+                let selected = name.is_eq(name_int)? & name_len.is_eq(len)?;
+                mask.push(&value_byte & selected);
+            }
+
+            let name_start = &line_start & !&cr;
+            let name_byte = &in_name & !&colon;
+            name = name_start.select(
+                &folded,
+                &name_byte.select(&(&name * F::from(256u16) + &folded), &name)?,
+            )?;
+            name_len =
+                name_start.select(&FpVar::one(), &(&name_len + FpVar::from(name_byte.clone())))?;
+            body_start_var += FpVar::from(!&done);
+
+            let next_cr = (&in_blank | &in_value) & &cr;
+            let next_blank_cr = &line_start & &cr;
+            in_blank = (&in_name & &colon) | (&in_blank & &blank);
+            in_value = value_byte;
+            in_name = name_start | name_byte;
+            done = &done | &blank_cr;
+            line_start = line_cr;
+            line_cr = next_cr;
+            blank_cr = next_blank_cr;
+        }
+        done.enforce_equal(&Boolean::TRUE)?;
+
+        Ok(Self {
+            data_vars: data_vars.to_vec(),
+            names: names.iter().map(|name| name.to_string()).collect(),
+            start,
+            body_start_var,
+            mask_vars,
+        })
+    }
+
+    /// The value of the header `name` after `prefix`, such as `Bearer `, of
+    /// at most `max_len` bytes; the header must be present.
+    pub fn value_var(
+        &self,
+        name: &str,
+        prefix: &[u8],
+        max_len: usize,
+    ) -> Result<SliceVar<F>, SynthesisError> {
+        let i = self
+            .names
+            .iter()
+            .position(|n| n == name)
+            .expect("unregistered name");
+        let (start_var, len_var) = mask_position(&self.mask_vars[i])?;
+        let prefix_len = F::from(prefix.len() as u64);
+        let slice = SliceVar::new(
+            &self.data_vars,
+            &(start_var + prefix_len),
+            &(len_var - prefix_len),
+            max_len,
+            prefix.len(),
+            1,
+        )?;
+        slice.enforce_lead(prefix)?;
+        slice.enforce_tail(b"\r")?;
+        Ok(slice)
+    }
+
+    /// The length of the body, which runs to the end of the message.
+    pub fn body_len_var(&self) -> FpVar<F> {
+        FpVar::Constant(F::from(self.data_vars.len() as u64)) - &self.body_start_var
+    }
+
+    /// Enforce that the `content-length` header, which must be among the
+    /// names, is the length of the body, so that the peer reads it all.
+    pub fn enforce_content_length(&self) -> Result<(), SynthesisError> {
+        self.value_var(CONTENT_LENGTH, b"", MAX_CONTENT_LENGTH_DIGITS)?
+            .decimal_var()?
+            .enforce_equal(&self.body_len_var())
+    }
+
+    /// The body shifted to the front, followed by zeros up to the length of
+    /// the shortest head, a blank line after the first line.
+    pub fn body_vars(&self) -> Result<Vec<UInt8<F>>, SynthesisError> {
+        let body = SliceVar::new(
+            &self.data_vars,
+            &self.body_start_var,
+            &self.body_len_var(),
+            self.data_vars.len() - self.start - 2,
+            0,
+            0,
+        )?;
+        body.byte_vars
+            .iter()
+            .map(|var| Ok(UInt8::from_bits_le(&enforce_bits(var, 8)?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Fr;
+    use ark_ff::PrimeField;
+    use ark_r1cs_std::{alloc::AllocVar, uint8::UInt8, R1CSVar};
+    use ark_relations::{ns, r1cs::ConstraintSystem};
+
+    use super::{native::parse_head, HeadVar};
+
+    const NAMES: [&str; 2] = ["host", "authorization"];
+
+    fn head_constraints(data: &[u8]) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let data_vars = data
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc data"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+        let head = HeadVar::new(&data_vars, 17, &NAMES).unwrap();
+        let host = head.value_var("host", b"", 16).unwrap();
+        let api_key = head.value_var("authorization", b"Bearer ", 4).unwrap();
+        let body = head.body_vars().unwrap();
+        if !cs.is_satisfied().unwrap() {
+            return false;
+        }
+
+        let native = parse_head(data, 17, &NAMES).unwrap();
+        assert_eq!(
+            head.body_start_var.value().unwrap(),
+            Fr::from(native.body_start as u64)
+        );
+        let len = host.len_var.value().unwrap().into_bigint().0[0] as usize;
+        assert_eq!(native.values[0].clone().unwrap().len(), len);
+        assert_eq!(api_key.byte_vars.value().unwrap(), b"k-12".map(Fr::from));
+        let body = body.value().unwrap();
+        assert_eq!(body[..2], *b"{}");
+        assert!(body[2..].iter().all(|b| *b == 0));
+        true
+    }
+
+    #[test]
+    fn test_head_constraint() {
+        assert!(head_constraints(
+            b"POST / HTTP/1.1\r\nHost:a.com\r\nAuthorization:Bearer k-12\r\n\r\n{}"
+        ));
+        assert!(head_constraints(
+            b"POST / HTTP/1.1\r\nuser-agent: x/1.0\r\nauthorization: Bearer k-12\r\nHOST:  a.com\r\naccept: */*\r\n\r\n{}"
+        ));
+        // no header may hide a pinned one, or be malformed
+        for data in [
+            &b"POST / HTTP/1.1\r\nHost:a.com\r\nHost:b.com\r\nAuthorization:Bearer k-12\r\n\r\n{}"
+                [..],
+            b"POST / HTTP/1.1\r\nHost :a.com\r\nAuthorization:Bearer k-12\r\n\r\n{}",
+            b"POST / HTTP/1.1\r\nHost:a.com\nAuthorization:Bearer k-12\r\n\r\n{}",
+            b"POST / HTTP/1.1\r\nHost:a.com\r\nAuthorization:Bearer k-12\r\n{}",
+        ] {
+            assert!(parse_head(data, 17, &NAMES).is_none());
+            assert!(!head_constraints(data));
+        }
+    }
+}
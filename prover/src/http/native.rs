@@ -0,0 +1,76 @@
+use std::ops::Range;
+
+/// The head of a request as located by `HeadVar`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Head {
+    /// The position of the body, right after the blank line.
+    pub body_start: usize,
+    /// The value of each header name, from its first non-blank byte to the
+    /// end of the line. An absent or empty header has no value.
+    pub values: Vec<Option<Range<usize>>>,
+}
+
+/// The native counterpart of `HeadVar`: the header lines of `data` from
+/// `start` up to the blank line, or `None` if the gadget would reject them.
+pub fn parse_head(data: &[u8], start: usize, names: &[&str]) -> Option<Head> {
+    let mut values = vec![None; names.len()];
+    let mut j = start;
+    loop {
+        let end = j + data.get(j..)?.iter().position(|b| *b == b'\r')?;
+        if data.get(end + 1) != Some(&b'\n') || data[j..end].contains(&b'\n') {
+            return None;
+        }
+        if end == j {
+            return Some(Head {
+                body_start: end + 2,
+                values,
+            });
+        }
+
+        let colon = j + data[j..end].iter().position(|b| *b == b':')?;
+        let name = &data[j..colon];
+        if name.is_empty() || name.iter().any(|b| matches!(b, b' ' | b'\t')) {
+            return None;
+        }
+        let value_start = (colon + 1..end)
+            .find(|v| !matches!(data[*v], b' ' | b'\t'))
+            .unwrap_or(end);
+
+        let folded = name.iter().map(|b| b | 0x20).collect::<Vec<_>>();
+        if let Some(i) = names.iter().position(|n| n.as_bytes() == folded) {
+            if value_start < end {
+                // the gadget takes one run of value bytes per name
+                if values[i].is_some() {
+                    return None;
+                }
+                values[i] = Some(value_start..end);
+            }
+        }
+        j = end + 2;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_head;
+
+    #[test]
+    fn test_parse_head() {
+        let data = b"POST / HTTP/1.1\r\nhost: a.com\r\nUser-Agent:x\r\nAUTHORIZATION: \t Bearer k \r\n\r\n{}";
+        let head = parse_head(data, 17, &["host", "authorization", "accept"]).unwrap();
+        assert_eq!(head.body_start, data.len() - 2);
+        assert_eq!(&data[head.values[0].clone().unwrap()], b"a.com");
+        assert_eq!(&data[head.values[1].clone().unwrap()], b"Bearer k ");
+        assert_eq!(head.values[2], None);
+
+        for data in [
+            &b"POST / HTTP/1.1\r\nhost: a.com\r\nhost: b.com\r\n\r\n"[..],
+            b"POST / HTTP/1.1\r\nhost : a.com\r\n\r\n",
+            b"POST / HTTP/1.1\r\nhost: a.com\n\r\n",
+            b"POST / HTTP/1.1\r\n: a.com\r\n\r\n",
+            b"POST / HTTP/1.1\r\nhost: a.com\r\n",
+        ] {
+            assert_eq!(parse_head(data, 17, &["host"]), None);
+        }
+    }
+}
@@ -0,0 +1,480 @@
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    prelude::Boolean,
+    uint8::UInt8,
+};
+use ark_relations::r1cs::SynthesisError;
+
+use crate::{
+    slice::{mask_position, SliceVar},
+    utils::{any, bytes_to_fp_var},
+};
+
+pub mod native;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathStep {
+    Key(String),
+    /// A key spelled either way, such as `system_instruction|systemInstruction`,
+    /// the object having at most one of them.
+    AnyKey(Vec<String>),
+    Index(usize),
+}
+
+/// A path to a value of a JSON document, such as `messages[0].content`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonPath(pub Vec<PathStep>);
+
+impl JsonPath {
+    /// Parse dot-separated keys, each followed by any number of `[index]`;
+    /// the aliases of a key are separated by `|`.
+    pub fn parse(s: &str) -> Self {
+        let mut steps = vec![];
+        for segment in s.split('.') {
+            let mut parts = segment.split('[');
+            let key = parts.next().unwrap();
+            if key.contains('|') {
+                steps.push(PathStep::AnyKey(
+                    key.split('|').map(str::to_string).collect(),
+                ));
+            } else if !key.is_empty() {
+                steps.push(PathStep::Key(key.to_string()));
+            }
+            for index in parts {
+                let index = index.strip_suffix(']').expect("unclosed index");
+                steps.push(PathStep::Index(index.parse().expect("invalid index")));
+            }
+        }
+        Self(steps)
+    }
+}
+
+/// The paths up to the last aliased key of each of `paths`, whose masks
+/// must have a single run so that no object spells a key both ways.
+fn alias_paths(paths: &[JsonPath]) -> Vec<JsonPath> {
+    let mut alias_paths = vec![];
+    for path in paths {
+        let Some(k) = path
+            .0
+            .iter()
+            .rposition(|step| matches!(step, PathStep::AnyKey(_)))
+        else {
+            continue;
+        };
+        let prefix = JsonPath(path.0[..=k].to_vec());
+        if !alias_paths.contains(&prefix) {
+            alias_paths.push(prefix);
+        }
+    }
+    alias_paths
+}
+
+/// The registers of one open container, see `native::Level`.
+#[derive(Clone)]
+struct LevelVar<F: PrimeField> {
+    array: Boolean<F>,
+    expect_key: Boolean<F>,
+    key_seen: Boolean<F>,
+    has_value: Boolean<F>,
+    ended: Boolean<F>,
+    after_comma: Boolean<F>,
+    index: FpVar<F>,
+    /// The key as a big-endian integer, and its length.
+    key: FpVar<F>,
+    key_len: FpVar<F>,
+}
+
+impl<F: PrimeField> LevelVar<F> {
+    fn empty() -> Self {
+        Self {
+            array: Boolean::FALSE,
+            expect_key: Boolean::FALSE,
+            key_seen: Boolean::FALSE,
+            has_value: Boolean::FALSE,
+            ended: Boolean::FALSE,
+            after_comma: Boolean::FALSE,
+            index: FpVar::zero(),
+            key: FpVar::zero(),
+            key_len: FpVar::zero(),
+        }
+    }
+
+    /// Whether the member being read has `key`.
+    fn is_key(&self, key: &str) -> Result<Boolean<F>, SynthesisError> {
+        assert!(key.len() < 32, "key too long");
+        let key_int = F::from_be_bytes_mod_order(key.as_bytes());
+        Ok(self.key.is_eq(&FpVar::Constant(key_int))?
+            & self
+                .key_len
+                .is_eq(&FpVar::Constant(F::from(key.len() as u64)))?)
+    }
+
+    /// Whether the member being read is the one selected by `step`.
+    fn selects(&self, step: &PathStep) -> Result<Boolean<F>, SynthesisError> {
+        match step {
+            PathStep::Key(key) => Ok(!&self.array & !&self.expect_key & self.is_key(key)?),
+            PathStep::AnyKey(keys) => {
+                let is_keys = keys
+                    .iter()
+                    .map(|key| self.is_key(key))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(!&self.array & !&self.expect_key & any(&is_keys)?)
+            }
+            PathStep::Index(i) => {
+                Ok(&self.array & self.index.is_eq(&FpVar::Constant(F::from(*i as u64)))?)
+            }
+        }
+    }
+}
+
+/// A streaming tokenizer over a JSON object, which locates the values of
+/// `paths` wherever they are, with any whitespace and key order.
+///
+/// Each byte updates the string state, the depth and the registers of up to
+/// `max_depth` open containers, selected by comparing the depth with each
+/// level. Brackets must balance and match, keys must be strings without
+/// escapes followed by `:`, members and elements must be separated by
+/// single commas, and nothing but whitespace, or the zeros padding a body
+/// shifted out of a request, may follow the closing brace. Scalars are not
+/// validated: a value is any run of non-structural bytes.
+pub struct JsonVar<F: PrimeField> {
+    pub body_vars: Vec<UInt8<F>>,
+    pub paths: Vec<JsonPath>,
+    /// For each path, whether each byte is part of its value.
+    mask_vars: Vec<Vec<Boolean<F>>>,
+}
+
+impl<F: PrimeField> JsonVar<F> {
+    pub fn new(
+        body_vars: &[UInt8<F>],
+        paths: &[JsonPath],
+        max_depth: usize,
+    ) -> Result<Self, SynthesisError> {
+        let byte_vars = bytes_to_fp_var(body_vars)?;
+        let mut levels = vec![LevelVar::empty(); max_depth + 1];
+        let mut in_str = Boolean::FALSE;
+        let mut esc = Boolean::FALSE;
+        let mut depth = FpVar::zero();
+        let all_paths = [paths, &alias_paths(paths)].concat();
+        let mut mask_vars = vec![vec![]; all_paths.len()];
+
+        for (j, b) in byte_vars.iter().enumerate() {
+            let is = |c: u8| b.is_eq(&FpVar::Constant(F::from(c)));
+            let quote = is(b'"')?;
+            let colon = is(b':')?;
+            let comma = is(b',')?;
+            let close_brace = is(b'}')?;
+            let close_bracket = is(b']')?;
+            let ws = is(b' ')? | is(b'\t')? | is(b'\n')? | is(b'\r')?;
+
+            // `at[l]` is set iff the depth is `l`, which bounds it too
+            let at = (0..=max_depth)
+                .map(|l| depth.is_eq(&FpVar::Constant(F::from(l as u64))))
+                .collect::<Result<Vec<_>, _>>()?;
+            at.iter()
+                .fold(FpVar::zero(), |sum, a| sum + FpVar::from(a.clone()))
+                .enforce_equal(&FpVar::one())?;
+            let mut current = LevelVar::<F>::empty();
+            for (a, level) in at.iter().zip(&levels).skip(1) {
+                current.array |= a & &level.array;
+                current.expect_key |= a & &level.expect_key;
+                current.key_seen |= a & &level.key_seen;
+                current.has_value |= a & &level.has_value;
+                current.ended |= a & &level.ended;
+                current.after_comma |= a & &level.after_comma;
+            }
+
+            let token = &in_str | !(&ws | &comma | &close_brace | &close_bracket | &colon);
+            for (path, mask) in all_paths.iter().zip(mask_vars.iter_mut()) {
+                let k = path.0.len();
+                assert!(k <= max_depth, "path deeper than the tokenizer");
+                let mut selected = !any(&at[..=k])? | (&at[k] & &token);
+                for (step, level) in path.0.iter().zip(&levels[1..]) {
+                    selected &= level.selects(step)?;
+                }
+                mask.push(selected);
+            }
+
+            let outside = !&in_str;
+            let live = &in_str & !&esc;
+            let closing = &live & &quote;
+            let opening = &outside & &quote;
+            let open_obj = &outside & is(b'{')?;
+            let open_arr = &outside & is(b'[')?;
+            let open = &open_obj | &open_arr;
+            let close_obj = &outside & &close_brace;
+            let close_arr = &outside & &close_bracket;
+            let comma = &outside & &comma;
+            let colon = &outside & &colon;
+
+            // the grammar, as checked by `native::value_masks`
+            let mut invalid = vec![
+                &current.expect_key & &outside & !(&ws | &quote | &colon | &comma | &close_brace),
+                // keys are compared raw, so `m\u0065ssages` would hide a
+                // second `messages` from the paths
+                &current.expect_key & &in_str & is(b'\\')?,
+                &opening & &current.expect_key & &current.key_seen,
+                &colon & !(&current.expect_key & &current.key_seen),
+                &close_obj & &current.array,
+                &close_arr & !&current.array,
+                &open & &at[max_depth],
+                // one value per member or element, between single commas
+                &current.ended & &token,
+                &comma & (&current.expect_key | !&current.has_value),
+                &close_obj & &current.expect_key & (&current.key_seen | &current.after_comma),
+                &close_obj & !&current.expect_key & !&current.has_value,
+                &close_arr & &current.after_comma & !&current.has_value,
+            ];
+            match j {
+                0 => open_obj.enforce_equal(&Boolean::TRUE)?,
+                _ => invalid.push(&at[0] & !(&ws | is(0)?)),
+            }
+            any(&invalid)?.enforce_equal(&Boolean::FALSE)?;
+
+            for l in 1..=max_depth {
+                let (here, level) = (&at[l], &levels[l]);
+                let opened = &at[l - 1] & &open;
+                let next_member = here & &comma & !&level.array;
+                let key_start = here & &level.expect_key & &opening;
+                let key_byte = here & &level.expect_key & &in_str & !&closing;
+
+                let expect_key = &next_member | (&level.expect_key & !(here & &colon));
+                let key_seen =
+                    (&level.key_seen | (here & &level.expect_key & &closing)) & !&next_member;
+                let index = &level.index + FpVar::from(here & &comma);
+                let key = key_byte.select(&(&level.key * F::from(256u16) + b), &level.key)?;
+                let key_len = &level.key_len + FpVar::from(key_byte.clone());
+
+                let in_value = here & !&level.expect_key;
+                let separated = here & &comma;
+                let child_closed = match at.get(l + 1) {
+                    Some(below) => below & (&close_obj | &close_arr),
+                    None => Boolean::FALSE,
+                };
+                let has_value = (&level.has_value | (&in_value & &token)) & !&separated;
+                let ended = (&level.ended
+                    | (&in_value & &level.has_value & &outside & &ws)
+                    | (&in_value & &closing)
+                    | child_closed)
+                    & !&separated;
+                let after_comma = &level.after_comma | &separated;
+
+                levels[l] = LevelVar {
+                    array: opened.select(&open_arr, &level.array)?,
+                    expect_key: opened.select(&open_obj, &expect_key)?,
+                    key_seen: key_seen & !&opened,
+                    has_value: has_value & !&opened,
+                    ended: ended & !&opened,
+                    after_comma: after_comma & !&opened,
+                    index: opened.select(&FpVar::zero(), &index)?,
+                    key: key_start.select(&FpVar::zero(), &key)?,
+                    key_len: key_start.select(&FpVar::zero(), &key_len)?,
+                };
+            }
+
+            in_str = &in_str ^ (&opening | &closing);
+            esc = &live & is(b'\\')?;
+            depth = depth + FpVar::from(open) - FpVar::from(&close_obj | &close_arr);
+        }
+
+        depth.enforce_equal(&FpVar::zero())?;
+        in_str.enforce_equal(&Boolean::FALSE)?;
+        for alias_mask in mask_vars.split_off(paths.len()) {
+            // enforces a single run, the position is unused
+            let _ = mask_position(&alias_mask)?;
+        }
+
+        Ok(Self {
+            body_vars: body_vars.to_vec(),
+            paths: paths.to_vec(),
+            mask_vars,
+        })
+    }
+
+    /// The raw value at `path`, with the quotes of a string, of at most
+    /// `max_len` bytes. An absent value has zero length.
+    pub fn value_var(
+        &self,
+        path: &JsonPath,
+        max_len: usize,
+    ) -> Result<SliceVar<F>, SynthesisError> {
+        let (start_var, len_var) = self.value_position(path)?;
+        SliceVar::new(&self.body_vars, &start_var, &len_var, max_len, 0, 0)
+    }
+
+    /// The content of the string at `path` as escaped in the body, of at most
+    /// `max_len` bytes; the value must be present.
+    pub fn string_var(
+        &self,
+        path: &JsonPath,
+        max_len: usize,
+    ) -> Result<SliceVar<F>, SynthesisError> {
+        let (start_var, len_var) = self.value_position(path)?;
+        let slice = SliceVar::new(
+            &self.body_vars,
+            &(start_var + F::one()),
+            &(len_var - F::from(2u8)),
+            max_len,
+            1,
+            1,
+        )?;
+        slice.enforce_lead(b"\"")?;
+        slice.enforce_tail(b"\"")?;
+        Ok(slice)
+    }
+
+    /// Enforce that there is no value at `path`.
+    pub fn enforce_absent(&self, path: &JsonPath) -> Result<(), SynthesisError> {
+        let (_, len_var) = self.value_position(path)?;
+        len_var.enforce_equal(&FpVar::zero())
+    }
+
+    /// The start and the length of the value at `path`.
+    fn value_position(&self, path: &JsonPath) -> Result<(FpVar<F>, FpVar<F>), SynthesisError> {
+        let i = self
+            .paths
+            .iter()
+            .position(|p| p == path)
+            .expect("unregistered path");
+        mask_position(&self.mask_vars[i])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Fr;
+    use ark_ff::PrimeField;
+    use ark_r1cs_std::{alloc::AllocVar, uint8::UInt8, R1CSVar};
+    use ark_relations::{ns, r1cs::ConstraintSystem};
+
+    use super::{native::value_masks, JsonPath, JsonVar, PathStep};
+
+    fn body_var(body: &[u8], paths: &[JsonPath]) -> (JsonVar<Fr>, bool) {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let body_vars = body
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc body"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+        let json = JsonVar::new(&body_vars, paths, 4).unwrap();
+        (json, cs.is_satisfied().unwrap())
+    }
+
+    fn to_bytes(bytes: &[Fr]) -> Vec<u8> {
+        bytes.iter().map(|b| b.into_bigint().0[0] as u8).collect()
+    }
+
+    #[test]
+    fn test_json_path() {
+        assert_eq!(
+            JsonPath::parse("messages[1].content").0,
+            vec![
+                PathStep::Key("messages".to_string()),
+                PathStep::Index(1),
+                PathStep::Key("content".to_string())
+            ]
+        );
+        assert_eq!(
+            JsonPath::parse("a|b.c").0,
+            vec![
+                PathStep::AnyKey(vec!["a".to_string(), "b".to_string()]),
+                PathStep::Key("c".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_aliased_key() {
+        let path = [JsonPath::parse("a|b.c")];
+        for (body, value) in [
+            (&br#"{"a":{"c":1}}"#[..], Some(&b"1"[..])),
+            (br#"{"x":0,"b":{"c":22}}"#, Some(b"22")),
+            // either spelling once only, even without the value
+            (br#"{"a":{"c":1},"b":{}}"#, None),
+        ] {
+            let (json, satisfied) = body_var(body, &path);
+            assert_eq!(satisfied, value.is_some());
+            assert_eq!(value_masks(body, &path, 4).is_some(), satisfied);
+            if let Some(value) = value {
+                let slice = json.value_var(&path[0], 4).unwrap();
+                assert_eq!(
+                    to_bytes(&slice.byte_vars.value().unwrap()[..value.len()]),
+                    value
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_json_constraint() {
+        let body =
+            br#"{"model": "gpt-4o-mini", "messages":[ {"content":"you are a \"zypher\" girl!",
+            "role":"system"},{"role":"user","content":"hi"}],"temperature" :0.7 , "n":{"a":[]}}"#;
+        let paths = [
+            "model",
+            "temperature",
+            "messages[0].content",
+            "messages[1].role",
+            "n",
+        ]
+        .map(JsonPath::parse);
+        let (json, satisfied) = body_var(body, &paths);
+        assert!(satisfied);
+
+        let masks = value_masks(body, &paths, 4).unwrap();
+        for (mask_vars, mask) in json.mask_vars.iter().zip(masks) {
+            assert_eq!(mask_vars.value().unwrap(), mask);
+        }
+
+        let model = json.string_var(&paths[0], 16).unwrap();
+        assert_eq!(
+            to_bytes(&model.byte_vars.value().unwrap()[..11]),
+            b"gpt-4o-mini"
+        );
+        assert_eq!(model.len_var.value().unwrap(), Fr::from(11u8));
+        let temperature = json.value_var(&paths[1], 8).unwrap();
+        assert_eq!(
+            to_bytes(&temperature.byte_vars.value().unwrap()[..3]),
+            b"0.7"
+        );
+        let prompt = json.string_var(&paths[2], 32).unwrap();
+        assert_eq!(prompt.len_var.value().unwrap(), Fr::from(26u8));
+        let object = json.value_var(&paths[4], 16).unwrap();
+        assert_eq!(
+            to_bytes(&object.byte_vars.value().unwrap()[..8]),
+            br#"{"a":[]}"#
+        );
+        assert!(json.body_vars.cs().is_satisfied().unwrap());
+
+        // a string is expected
+        json.string_var(&paths[1], 8).unwrap();
+        assert!(!json.body_vars.cs().is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        let model = [JsonPath::parse("model")];
+        for body in [
+            r#"{"model":"a"]"#,
+            r#"{"model":"a"} x"#,
+            r#"{"model" "b":"a"}"#,
+            r#"{model:"a"}"#,
+            r#"{"model":"a}"#,
+            r#"["model"]"#,
+            r#"{"a":[[[[1]]]]}"#,
+            r#"{"model":"a","m\u006fdel":"b"}"#,
+            r#"{,"model":"a"}"#,
+            r#"{"model":"a",}"#,
+            r#"{"model":"a" "b":2}"#,
+            r#"{"model":"a" 2}"#,
+            r#"{"model":}"#,
+            r#"{"model":"a","b":[1,,2]}"#,
+            r#"{"model":"a","b":[1,]}"#,
+            r#"{"model":"a","b":[1 2]}"#,
+        ] {
+            assert!(value_masks(body.as_bytes(), &model, 4).is_none());
+            assert!(!body_var(body.as_bytes(), &model).1, "{body}");
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use std::ops::Range;
+
+use super::{alias_paths, JsonPath, PathStep};
+
+/// One open container of the tokenizer.
+#[derive(Debug, Clone, Default)]
+struct Level {
+    array: bool,
+    /// In an object, whether the member key is being read.
+    expect_key: bool,
+    /// Whether the member key string has been closed.
+    key_seen: bool,
+    /// Whether the current member or element has a value, and whether that
+    /// value has ended, so that only a comma or the close may follow.
+    has_value: bool,
+    ended: bool,
+    /// Whether a comma has been read, so that the container is not empty.
+    after_comma: bool,
+    index: usize,
+    key: Vec<u8>,
+}
+
+fn is_ws(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\r')
+}
+
+/// The native counterpart of `JsonVar`: for each path, whether each byte
+/// of `body` is part of its value, or `None` if the gadget would reject
+/// the body.
+pub fn value_masks(body: &[u8], paths: &[JsonPath], max_depth: usize) -> Option<Vec<Vec<bool>>> {
+    let mut levels: Vec<Level> = vec![];
+    let (mut in_str, mut esc) = (false, false);
+    let all_paths = [paths, &alias_paths(paths)].concat();
+    let mut masks = vec![vec![]; all_paths.len()];
+
+    for (j, &b) in body.iter().enumerate() {
+        let d = levels.len();
+        let token = in_str || !(is_ws(b) || matches!(b, b',' | b'}' | b']' | b':'));
+        for (path, mask) in all_paths.iter().zip(masks.iter_mut()) {
+            let k = path.0.len();
+            let selected = d >= k
+                && path.0.iter().zip(&levels).all(|(step, level)| match step {
+                    PathStep::Key(key) => {
+                        !level.array && !level.expect_key && level.key == key.as_bytes()
+                    }
+                    PathStep::AnyKey(keys) => {
+                        !level.array
+                            && !level.expect_key
+                            && keys.iter().any(|key| level.key == key.as_bytes())
+                    }
+                    PathStep::Index(i) => level.array && level.index == *i,
+                })
+                && (d > k || token);
+            mask.push(selected);
+        }
+
+        if in_str {
+            let level = levels.last_mut().unwrap();
+            let in_key = level.expect_key;
+            if in_key && b == b'\\' {
+                return None;
+            }
+            match (esc, b) {
+                (true, _) => esc = false,
+                (false, b'\\') => esc = true,
+                (false, b'"') => {
+                    in_str = false;
+                    level.key_seen |= in_key;
+                    level.ended |= !in_key;
+                    continue;
+                }
+                _ => {}
+            }
+            if in_key {
+                level.key.push(b);
+            }
+            continue;
+        }
+
+        if d == 0 {
+            match (j, b) {
+                (0, b'{') => {}
+                (0, _) => return None,
+                _ if is_ws(b) || b == 0 => continue,
+                _ => return None,
+            }
+        } else {
+            let level = &mut levels[d - 1];
+            if level.expect_key && !(is_ws(b) || matches!(b, b'"' | b':' | b',' | b'}')) {
+                return None;
+            }
+            if token && level.ended {
+                return None;
+            }
+            if !level.expect_key {
+                level.ended |= level.has_value && is_ws(b);
+                level.has_value |= token;
+            }
+        }
+
+        match b {
+            b'"' => {
+                let level = levels.last_mut().unwrap();
+                if level.expect_key {
+                    if level.key_seen {
+                        return None;
+                    }
+                    level.key.clear();
+                }
+                in_str = true;
+            }
+            b'{' | b'[' => {
+                if d == max_depth {
+                    return None;
+                }
+                levels.push(Level {
+                    array: b == b'[',
+                    expect_key: b == b'{',
+                    ..Default::default()
+                });
+            }
+            b'}' | b']' => {
+                let level = levels.pop().unwrap();
+                let invalid = match (level.array, b) {
+                    (false, b'}') if level.expect_key => level.key_seen || level.after_comma,
+                    (false, b'}') => !level.has_value,
+                    (true, b']') => level.after_comma && !level.has_value,
+                    _ => true,
+                };
+                if invalid {
+                    return None;
+                }
+                if let Some(parent) = levels.last_mut() {
+                    parent.ended = true;
+                }
+            }
+            b',' => {
+                let level = levels.last_mut().unwrap();
+                if level.expect_key || !level.has_value {
+                    return None;
+                }
+                level.has_value = false;
+                level.ended = false;
+                level.after_comma = true;
+                if level.array {
+                    level.index += 1;
+                } else {
+                    level.expect_key = true;
+                    level.key_seen = false;
+                }
+            }
+            b':' => {
+                let level = levels.last_mut().unwrap();
+                if !(level.expect_key && level.key_seen) {
+                    return None;
+                }
+                level.expect_key = false;
+            }
+            _ => {}
+        }
+    }
+
+    // no key spelled both ways, as `mask_position` enforces
+    let alias_masks = masks.split_off(paths.len());
+    let num_runs = |mask: &[bool]| {
+        (0..mask.len())
+            .filter(|&j| mask[j] && (j == 0 || !mask[j - 1]))
+            .count()
+    };
+    match levels.is_empty() && !in_str && alias_masks.iter().all(|mask| num_runs(mask) <= 1) {
+        true => Some(masks),
+        false => None,
+    }
+}
+
+/// The position of the raw value at `path` in `body`, with the quotes of a
+/// string.
+pub fn value_range(body: &[u8], path: &JsonPath, max_depth: usize) -> Option<Range<usize>> {
+    let mask = value_masks(body, std::slice::from_ref(path), max_depth)?.remove(0);
+    let start = mask.iter().position(|m| *m)?;
+    let len = mask[start..].iter().take_while(|m| **m).count();
+    Some(start..start + len)
+}
+
+/// The position of the content of the string at `path` in `body`, as
+/// escaped in the body.
+pub fn string_range(body: &[u8], path: &JsonPath, max_depth: usize) -> Option<Range<usize>> {
+    let range = value_range(body, path, max_depth)?;
+    match range.len() >= 2 && body[range.start] == b'"' {
+        true => Some(range.start + 1..range.end - 1),
+        false => None,
+    }
+}
+
+/// The non-negative number `value` without exponent, in fixed point with
+/// `decimals` digits after the point, or `None` if it has more or is not
+/// such a number.
+pub fn parse_number(value: &[u8], decimals: usize) -> Option<u64> {
+    let (int, frac) = match value.iter().position(|b| *b == b'.') {
+        Some(i) => (&value[..i], &value[i + 1..]),
+        None => (value, &[][..]),
+    };
+    let is_digits = |s: &[u8]| !s.is_empty() && s.iter().all(u8::is_ascii_digit);
+    if !is_digits(int) || (int.len() < value.len() && !is_digits(frac)) || frac.len() > decimals {
+        return None;
+    }
+
+    let mut number = 0u64;
+    for b in int.iter().chain(frac) {
+        number = number.checked_mul(10)?.checked_add((b - b'0') as u64)?;
+    }
+    number.checked_mul(10u64.checked_pow((decimals - frac.len()) as u32)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_number, string_range, value_range};
+    use crate::json::JsonPath;
+
+    #[test]
+    fn test_value_range() {
+        let body = br#"{ "model" : "gpt-4o", "messages": [
+            {"role": "system", "content": "be \"brief\""},
+            {"content": "hi", "role": "user"}
+        ], "temperature":0.7, "n": {"a": [1, {"b": 2}]}}"#;
+        let get = |path: &str| {
+            let range = value_range(body, &JsonPath::parse(path), 4).unwrap();
+            String::from_utf8(body[range].to_vec()).unwrap()
+        };
+        assert_eq!(get("model"), "\"gpt-4o\"");
+        assert_eq!(get("temperature"), "0.7");
+        assert_eq!(get("messages[0].content"), r#""be \"brief\"""#);
+        assert_eq!(get("messages[1].role"), "\"user\"");
+        assert_eq!(get("n"), r#"{"a": [1, {"b": 2}]}"#);
+        assert_eq!(get("n.a[1].b"), "2");
+
+        let range = string_range(body, &JsonPath::parse("messages[1].content"), 4).unwrap();
+        assert_eq!(&body[range], b"hi");
+        assert!(value_range(body, &JsonPath::parse("messages[2]"), 4).is_none());
+        // too deep
+        assert!(value_range(body, &JsonPath::parse("model"), 3).is_none());
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number(b"0", 2), Some(0));
+        assert_eq!(parse_number(b"0.7", 2), Some(70));
+        assert_eq!(parse_number(b"1.25", 2), Some(125));
+        assert_eq!(parse_number(b"42", 0), Some(42));
+        for value in ["0.125", "1.", ".5", "1.2.3", "-1", "1e3", ""] {
+            assert_eq!(parse_number(value.as_bytes(), 2), None, "{value}");
+        }
+    }
+
+    #[test]
+    fn test_invalid_body() {
+        let model = JsonPath::parse("model");
+        for body in [
+            r#"{"model":"a"]"#,
+            r#"{"model":"a"} x"#,
+            r#"{"model" "b":"a"}"#,
+            r#"{model:"a"}"#,
+            r#"{"model":"a}"#,
+            r#"["model"]"#,
+            r#"{"m\u006fdel":"a"}"#,
+            r#"{,"model":"a"}"#,
+            r#"{"model":"a",}"#,
+            r#"{"model":"a" "b":2}"#,
+        ] {
+            assert!(value_range(body.as_bytes(), &model, 4).is_none(), "{body}");
+        }
+    }
+}
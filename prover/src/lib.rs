@@ -1,5 +1,24 @@
+pub mod aes;
+pub mod anthropic;
 pub mod build_cs;
 pub mod chacha20;
+pub mod commitment;
+pub mod exchange;
+pub mod gemini;
+pub mod hkdf;
+pub mod http;
+pub mod json;
 pub mod mimc;
 pub mod openai;
+pub mod poly1305;
+pub mod poseidon;
+pub mod prover;
+pub mod provider;
+pub mod record;
+pub mod request;
+pub mod response;
+pub mod sha256;
+pub mod slice;
+pub mod suite;
+pub mod template;
 pub mod utils;
@@ -30,7 +30,7 @@ impl<F: PrimeField> MimcBn254Var<F> {
         let mut r = FpVar::zero();
         let mut c = FpVar::zero();
         for s in state.iter() {
-            r = r + s;
+            r += s;
             (r, c) = self.feistel(r, c);
         }
         let mut outputs = vec![r.clone()];
@@ -46,6 +46,17 @@ impl<F: PrimeField> MimcBn254Var<F> {
         }
     }
 
+    /// The gadget counterpart of `MiMC::sponge`, with the length as a
+    /// variable so that it may be private.
+    pub fn sponge(&self, domain: F, len_var: &FpVar<F>, state: &[FpVar<F>]) -> FpVar<F> {
+        let (mut r, mut c) = self.feistel(len_var.clone(), FpVar::Constant(domain));
+        for s in state.iter() {
+            r += s;
+            (r, c) = self.feistel(r, c);
+        }
+        r
+    }
+
     fn feistel(&self, left: FpVar<F>, right: FpVar<F>) -> (FpVar<F>, FpVar<F>) {
         let mut x_l = left;
         let mut x_r = right;
@@ -103,4 +114,30 @@ mod test {
         );
         assert!(cs.is_satisfied().unwrap());
     }
+
+    #[test]
+    fn test_mimc_sponge() {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let mut rand = test_rng();
+
+        let mut round_constant_vars = vec![];
+        for c in MimcBn254::ROUND_KEYS {
+            round_constant_vars.push(FpVar::new_constant(ns!(cs, "alloc round keys"), c).unwrap());
+        }
+        let mimc = MimcBn254Var::new(1, &round_constant_vars, FpVar::zero());
+
+        let input = (0..3).map(|_| Fr::rand(&mut rand)).collect::<Vec<_>>();
+        let input_vars = input
+            .iter()
+            .map(|x| FpVar::new_witness(ns!(cs, "alloc input"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+        let len_var = FpVar::new_witness(ns!(cs, "alloc len"), || Ok(Fr::from(90u8))).unwrap();
+
+        let output_var = mimc.sponge(Fr::from(7u8), &len_var, &input_vars);
+        assert_eq!(
+            output_var.value().unwrap(),
+            MimcBn254::sponge(Fr::from(7u8), 90, &input)
+        );
+        assert!(cs.is_satisfied().unwrap());
+    }
 }
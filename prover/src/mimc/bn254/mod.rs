@@ -257,4 +257,18 @@ mod test {
             "12525131868496031425744154881744336661020056362076131525086600857748260152186"
         );
     }
+
+    #[test]
+    fn test_mimc_sponge() {
+        let state = [Fr::ONE, Fr::from(2u8)];
+        let padded = [Fr::ONE, Fr::from(2u8), Fr::ZERO];
+        assert_ne!(
+            MimcBn254::sponge(Fr::ONE, 2, &state),
+            MimcBn254::sponge(Fr::ONE, 3, &padded)
+        );
+        assert_ne!(
+            MimcBn254::sponge(Fr::ONE, 2, &state),
+            MimcBn254::sponge(Fr::from(2u8), 2, &state)
+        );
+    }
 }
@@ -10,7 +10,7 @@ pub trait MiMC<F: PrimeField, const ROUNDS: usize, const EXPONENT: usize> {
         let mut r = F::zero();
         let mut c = F::zero();
         for s in state.iter() {
-            r = r + s;
+            r += s;
             (r, c) = Self::feistel(r, c);
         }
         let mut outputs = vec![r];
@@ -26,6 +26,18 @@ pub trait MiMC<F: PrimeField, const ROUNDS: usize, const EXPONENT: usize> {
         }
     }
 
+    /// Sponge mode with the capacity initialised to `domain` and the input
+    /// length absorbed before `state`, so that inputs differing only in
+    /// trailing zero elements, or in domain, never collide.
+    fn sponge(domain: F, len: u64, state: &[F]) -> F {
+        let (mut r, mut c) = Self::feistel(F::from(len), domain);
+        for s in state.iter() {
+            r += s;
+            (r, c) = Self::feistel(r, c);
+        }
+        r
+    }
+
     fn feistel(left: F, right: F) -> (F, F) {
         let mut x_l = left;
         let mut x_r = right;
@@ -1 +1,27 @@
-pub mod req;
+use crate::{json::JsonPath, provider::Layout};
+
+pub mod resp;
+
+/// Chat completions: a bearer token, and the system prompt as the first of
+/// `messages`.
+pub fn layout() -> Layout {
+    Layout {
+        api_key_header: "authorization",
+        api_key_prefix: b"Bearer ",
+        pinned_headers: vec![],
+        prompt: JsonPath::parse("messages[0].content"),
+        prompt_role: Some((JsonPath::parse("messages[0].role"), b"system")),
+        message_paths: |i| {
+            [
+                JsonPath::parse(&format!("messages[{i}].role")),
+                JsonPath::parse(&format!("messages[{i}].content")),
+            ]
+        },
+        prompt_excluded: vec![],
+        message_excluded: |_| vec![],
+        tool_paths: ["tools", "tool_choice", "response_format"]
+            .map(JsonPath::parse)
+            .to_vec(),
+        max_json_depth: 4,
+    }
+}
@@ -0,0 +1,176 @@
+use ark_ff::PrimeField;
+use ark_r1cs_std::uint8::UInt8;
+use ark_relations::r1cs::SynthesisError;
+use traits::RespConstraint;
+
+use crate::{
+    http::{
+        native::{parse_head, Head},
+        HeadVar, CONTENT_LENGTH, CONTENT_TYPE,
+    },
+    json::{native::string_range, JsonPath, JsonVar},
+    slice::SliceVar,
+    template::ResponseTemplate,
+    utils::enforce_equals,
+};
+
+pub mod traits;
+
+/// A chat completion response, whose body is located after the head.
+pub struct RespVar<F: PrimeField> {
+    pub data_vars: Vec<UInt8<F>>,
+    pub template: ResponseTemplate,
+}
+
+/// The path of the output in the body.
+const OUTPUT: &str = "choices[0].message.content";
+/// Deep enough for the arrays in `message`, such as `annotations`.
+const MAX_JSON_DEPTH: usize = 5;
+const HEADER_NAMES: [&str; 2] = [CONTENT_TYPE, CONTENT_LENGTH];
+
+impl<F: PrimeField> RespVar<F> {
+    pub fn new(data_vars: &[UInt8<F>], template: &ResponseTemplate) -> Self {
+        Self {
+            data_vars: data_vars.to_vec(),
+            template: template.clone(),
+        }
+    }
+
+    /// The head of the response `plain_texts`, located as by
+    /// `generate_constraints`.
+    pub fn head(&self, plain_texts: &[u8]) -> Option<Head> {
+        parse_head(plain_texts, self.status_line().len(), &HEADER_NAMES)
+    }
+
+    /// The output of the response `plain_texts`, as escaped in the body.
+    pub fn output<'a>(&self, plain_texts: &'a [u8]) -> Option<&'a [u8]> {
+        let body = plain_texts.get(self.head(plain_texts)?.body_start..)?;
+        let range = string_range(body, &JsonPath::parse(OUTPUT), MAX_JSON_DEPTH)?;
+        Some(&body[range])
+    }
+
+    /// Tokenize the body, shifted out of the response.
+    pub fn body_var(&self, head: &HeadVar<F>) -> Result<JsonVar<F>, SynthesisError> {
+        JsonVar::new(
+            &head.body_vars()?,
+            &[JsonPath::parse(OUTPUT)],
+            MAX_JSON_DEPTH,
+        )
+    }
+
+    /// The output, of at most `max_output_len` bytes; its start is relative
+    /// to the body.
+    pub fn output_var(&self, body: &JsonVar<F>) -> Result<SliceVar<F>, SynthesisError> {
+        body.string_var(&JsonPath::parse(OUTPUT), self.template.max_output_len)
+    }
+}
+
+impl<F: PrimeField> RespConstraint<F> for RespVar<F> {
+    fn status_line(&self) -> Vec<u8> {
+        format!("{}\r\n", self.template.status_line)
+            .as_bytes()
+            .to_vec()
+    }
+
+    fn pinned_headers(&self) -> Vec<(&'static str, Vec<u8>)> {
+        vec![(CONTENT_TYPE, b"application/json".to_vec())]
+    }
+
+    fn generate_constraints(&self) -> Result<HeadVar<F>, SynthesisError> {
+        let status_line = self.status_line();
+        let status_line_vars = status_line
+            .iter()
+            .map(|x| UInt8::constant(*x))
+            .collect::<Vec<UInt8<F>>>();
+        enforce_equals(&status_line_vars, &self.data_vars[..status_line.len()])?;
+
+        let head = HeadVar::new(&self.data_vars, status_line.len(), &HEADER_NAMES)?;
+        for (name, value) in self.pinned_headers() {
+            head.value_var(name, b"", value.len())?
+                .enforce_bytes(&value)?;
+        }
+        head.enforce_content_length()?;
+        Ok(head)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use ark_bn254::Fr;
+    use ark_ff::PrimeField;
+    use ark_r1cs_std::{alloc::AllocVar, uint8::UInt8, R1CSVar};
+    use ark_relations::{ns, r1cs::ConstraintSystem};
+
+    use super::traits::RespConstraint;
+    use crate::{openai::resp::RespVar, template::ResponseTemplate};
+
+    pub(crate) const RESPONSE: &str = "485454502f312e3120323030204f4b0d0a436f6e74656e742d547970653a206170706c69636174696f6e2f6a736f6e0d0a436f6e74656e742d4c656e6774683a203333390d0a436f6e6e656374696f6e3a20636c6f73650d0a0d0a7b0a2020226964223a202263686174636d706c2d416b5a3165565a377051345272336b39575a7832222c0a2020226f626a656374223a2022636861742e636f6d706c6574696f6e222c0a20202263726561746564223a20313733353638393630302c0a2020226d6f64656c223a20226770742d346f2d6d696e692d323032342d30372d3138222c0a20202263686f69636573223a205b0a202020207b0a20202020202022696e646578223a20302c0a202020202020226d657373616765223a207b0a202020202020202022726f6c65223a2022617373697374616e74222c0a202020202020202022636f6e74656e74223a20224d79206e616d65206973205a79706865722e222c0a2020202020202020227265667573616c223a206e756c6c0a2020202020207d2c0a2020202020202266696e6973685f726561736f6e223a202273746f70220a202020207d0a20205d0a7d0a";
+
+    /// Whether `bytes` satisfies the response constraints, checking the
+    /// output against the native one if so.
+    fn response_constraints(bytes: &[u8]) -> bool {
+        let template =
+            ResponseTemplate::from_toml(include_str!("../../../templates/openai_response.toml"))
+                .unwrap();
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let byte_vars = bytes
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc data"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+
+        let var = RespVar::new(&byte_vars, &template);
+        let head = var.generate_constraints().unwrap();
+        let output = var.output_var(&var.body_var(&head).unwrap()).unwrap();
+        if !cs.is_satisfied().unwrap() {
+            return false;
+        }
+
+        let native = var.output(bytes).unwrap();
+        let len = output.len_var.value().unwrap().into_bigint().0[0] as usize;
+        let output = output.byte_vars.value().unwrap();
+        assert_eq!(
+            output[..len],
+            native.iter().map(|b| Fr::from(*b)).collect::<Vec<_>>()
+        );
+        true
+    }
+
+    #[test]
+    fn test_resp_constraint() {
+        let bytes = hex::decode(RESPONSE).unwrap();
+        assert!(response_constraints(&bytes));
+        let template =
+            ResponseTemplate::from_toml(include_str!("../../../templates/openai_response.toml"))
+                .unwrap();
+        assert_eq!(
+            RespVar::<Fr>::new(&[], &template).output(&bytes),
+            Some(&b"My name is Zypher."[..])
+        );
+
+        let text = String::from_utf8(bytes).unwrap();
+        let rewrite = |from: &str, to: &str| {
+            let body = text.split("\r\n\r\n").nth(1).unwrap().replace(from, to);
+            let head = text
+                .split("\r\n\r\n")
+                .next()
+                .unwrap()
+                .replace("339", &body.len().to_string());
+            format!("{head}\r\n\r\n{body}").into_bytes()
+        };
+        // the output is found by its path wherever the body puts it, and
+        // ends at its closing quote
+        assert!(response_constraints(&rewrite(
+            "\"role\": \"assistant\",",
+            "\"role\": \"assistant\", \"annotations\": [],"
+        )));
+        assert!(response_constraints(&rewrite("Zypher.", "Zy\\\"pher.")));
+        // another status, a truncated body, an output in another choice
+        for bytes in [
+            text.replace("200 OK", "201 OK").into_bytes(),
+            text.replace("339", "340").into_bytes(),
+            rewrite("\"index\": 0,", "\"index\": 0, \"x\": {}}, {"),
+        ] {
+            assert!(!response_constraints(&bytes));
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{
+    alloc::AllocVar, convert::ToBitsGadget, eq::EqGadget, fields::fp::FpVar, prelude::Boolean,
+    uint8::UInt8, R1CSVar,
+};
+use ark_relations::{ns, r1cs::SynthesisError};
+use native::{Fq, CLAMP};
+
+use crate::utils::alloc_bits;
+
+pub mod native;
+
+/// Limb width used to split the 130-bit accumulator and the 124-bit `r`, so
+/// that every partial product stays far below the native modulus.
+const LIMB_BITS: usize = 65;
+
+/// Poly1305 over a native field of at least 200 bits.
+///
+/// The accumulator is kept as 130 bits, and every multiplication by `r` is
+/// reduced with a witnessed quotient: `(h + n) * r = q * p + h'`, with both
+/// sides bounded by 2^200 so the equation holds over the integers.
+pub struct Poly1305Var<F: PrimeField> {
+    pub r_bits: Vec<Boolean<F>>,
+    pub s_bits: Vec<Boolean<F>>,
+}
+
+impl<F: PrimeField> Poly1305Var<F> {
+    /// Split and clamp the 32-byte one-time key.
+    pub fn new(key_vars: &[UInt8<F>]) -> Result<Self, SynthesisError> {
+        assert_eq!(key_vars.len(), 32);
+        assert!(F::MODULUS_BIT_SIZE > 200);
+
+        let mut r_bits = vec![];
+        for (var, mask) in key_vars[..16].iter().zip(CLAMP.iter()) {
+            for (i, bit) in var.to_bits_le()?.into_iter().enumerate() {
+                match (mask >> i) & 1 == 1 {
+                    true => r_bits.push(bit),
+                    false => r_bits.push(Boolean::FALSE),
+                }
+            }
+        }
+
+        let mut s_bits = vec![];
+        for var in key_vars[16..].iter() {
+            s_bits.extend(var.to_bits_le()?);
+        }
+
+        Ok(Self { r_bits, s_bits })
+    }
+
+    /// Return the 16-byte tag of `msg_vars`.
+    pub fn generate_constraints(
+        &self,
+        msg_vars: &[UInt8<F>],
+    ) -> Result<Vec<UInt8<F>>, SynthesisError> {
+        let cs = msg_vars.cs().or(self.r_bits.cs());
+        let p = modulus::<F>();
+        let shift = F::from(2u8).pow([LIMB_BITS as u64]);
+
+        let r0 = Boolean::le_bits_to_fp(&self.r_bits[..LIMB_BITS])?;
+        let r1 = Boolean::le_bits_to_fp(&self.r_bits[LIMB_BITS..])?;
+
+        let mut h_bits = vec![Boolean::FALSE; 130];
+        for block in msg_vars.chunks(16) {
+            let mut n_bits = vec![];
+            for var in block {
+                n_bits.extend(var.to_bits_le()?);
+            }
+            n_bits.push(Boolean::TRUE);
+            n_bits.resize(130, Boolean::FALSE);
+
+            let x0 = Boolean::le_bits_to_fp(&h_bits[..LIMB_BITS])?
+                + Boolean::le_bits_to_fp(&n_bits[..LIMB_BITS])?;
+            let x1 = Boolean::le_bits_to_fp(&h_bits[LIMB_BITS..])?
+                + Boolean::le_bits_to_fp(&n_bits[LIMB_BITS..])?;
+
+            // 2^130 = 5 (mod p) folds the high limb product back into the low one
+            let low = &x0 * &r0 + (&x1 * &r1) * F::from(5u8);
+            let high = &x0 * &r1 + &x1 * &r0;
+            let product = low + high * shift;
+
+            let value = product.value().map(|v| reduce_mod_p(&v));
+            let q_bits = alloc_bits(cs.clone(), value.map(|(q, _)| q), 70)?;
+            h_bits = alloc_bits(cs.clone(), value.map(|(_, h)| h), 130)?;
+
+            let rhs = Boolean::le_bits_to_fp(&q_bits)? * p + Boolean::le_bits_to_fp(&h_bits)?;
+            product.enforce_equal(&rhs)?;
+        }
+
+        // h < 2^130 < 2p, so at most one subtraction of p is needed, and
+        // h' < p iff h' + 5 fits in 130 bits
+        let h = Boolean::le_bits_to_fp(&h_bits)?;
+        let value = h.value().map(|v| reduce_mod_p(&v));
+        let c = Boolean::new_witness(ns!(cs, "alloc carry"), || value.map(|(q, _)| q == F::one()))?;
+        let freeze_bits = alloc_bits(cs.clone(), value.map(|(_, h)| h), 130)?;
+        let freeze = Boolean::le_bits_to_fp(&freeze_bits)?;
+        h.enforce_equal(&(&freeze + FpVar::from(c) * p))?;
+        let bound_bits = alloc_bits(cs.clone(), value.map(|(_, h)| h + F::from(5u8)), 130)?;
+        Boolean::le_bits_to_fp(&bound_bits)?.enforce_equal(&(&freeze + F::from(5u8)))?;
+
+        let tag =
+            Boolean::le_bits_to_fp(&freeze_bits[..128])? + Boolean::le_bits_to_fp(&self.s_bits)?;
+        let tag_bits = alloc_bits(cs.clone(), tag.value(), 129)?;
+        tag.enforce_equal(&Boolean::le_bits_to_fp(&tag_bits)?)?;
+
+        Ok(tag_bits[..128].chunks(8).map(UInt8::from_bits_le).collect())
+    }
+}
+
+/// The gadget counterpart of `native::aead_mac_data`.
+pub fn aead_mac_data<F: PrimeField>(
+    aad_vars: &[UInt8<F>],
+    cipher_vars: &[UInt8<F>],
+) -> Vec<UInt8<F>> {
+    let mut data_vars = aad_vars.to_vec();
+    data_vars.resize(aad_vars.len().div_ceil(16) * 16, UInt8::constant(0));
+    data_vars.extend_from_slice(cipher_vars);
+    data_vars.resize(data_vars.len().div_ceil(16) * 16, UInt8::constant(0));
+    for len in [aad_vars.len(), cipher_vars.len()] {
+        data_vars.extend((len as u64).to_le_bytes().map(UInt8::constant));
+    }
+    data_vars
+}
+
+fn modulus<F: PrimeField>() -> F {
+    F::from(2u8).pow([130]) - F::from(5u8)
+}
+
+/// Split an integer `v` < 2^200 into `(v / p, v % p)`.
+fn reduce_mod_p<F: PrimeField>(v: &F) -> (F, F) {
+    let bytes = v.into_bigint().to_bytes_le();
+    let h = F::from_le_bytes_mod_order(
+        &Fq::from_le_bytes_mod_order(&bytes)
+            .into_bigint()
+            .to_bytes_le(),
+    );
+    let q = (*v - h) * modulus::<F>().inverse().unwrap();
+    (q, h)
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, uint8::UInt8, R1CSVar};
+    use ark_relations::{ns, r1cs::ConstraintSystem};
+
+    use super::{native::mac, Poly1305Var};
+
+    #[test]
+    fn test_poly1305_constraint() {
+        let key = hex::decode("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b")
+            .unwrap();
+        let msg = b"Cryptographic Forum Research Group";
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let key_vars = key
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc key"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+        let msg_vars = msg
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc msg"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+
+        let poly1305 = Poly1305Var::new(&key_vars).unwrap();
+        let tag_vars = poly1305.generate_constraints(&msg_vars).unwrap();
+        let tag = tag_vars.value().unwrap();
+        assert_eq!(hex::encode(&tag), "a8061dc1305136c6c22b8baf0c0127a9");
+        assert!(cs.is_satisfied().unwrap());
+
+        // an all-ones key drives the accumulator close to 2^130
+        let key = vec![0xff; 32];
+        let msg = vec![0xff; 80];
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let key_vars = key
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc key"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+        let msg_vars = msg
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc msg"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+        let tag_vars = Poly1305Var::new(&key_vars)
+            .unwrap()
+            .generate_constraints(&msg_vars)
+            .unwrap();
+        assert_eq!(tag_vars.value().unwrap(), mac(&key, &msg));
+        assert!(cs.is_satisfied().unwrap());
+    }
+}
@@ -0,0 +1,102 @@
+use ark_ff::{
+    fields::{Fp192, MontBackend, MontConfig},
+    BigInteger, PrimeField,
+};
+
+use crate::chacha20::native::block;
+
+/// The Poly1305 prime field, p = 2^130 - 5.
+#[derive(MontConfig)]
+#[modulus = "1361129467683753853853498429727072845819"]
+#[generator = "2"]
+pub struct FqConfig;
+pub type Fq = Fp192<MontBackend<FqConfig, 3>>;
+
+/// Mask applied to `r`, the first half of the one-time key (RFC 8439, 2.5).
+pub const CLAMP: [u8; 16] = [
+    0xff, 0xff, 0xff, 0x0f, 0xfc, 0xff, 0xff, 0x0f, 0xfc, 0xff, 0xff, 0x0f, 0xfc, 0xff, 0xff, 0x0f,
+];
+
+pub fn mac(key: &[u8], msg: &[u8]) -> Vec<u8> {
+    assert_eq!(key.len(), 32);
+
+    let r = key[..16]
+        .iter()
+        .zip(CLAMP.iter())
+        .map(|(k, c)| k & c)
+        .collect::<Vec<_>>();
+    let r = Fq::from_le_bytes_mod_order(&r);
+
+    let mut acc = Fq::from(0u8);
+    for block in msg.chunks(16) {
+        let mut n = block.to_vec();
+        n.push(1);
+        acc = (acc + Fq::from_le_bytes_mod_order(&n)) * r;
+    }
+
+    let mut acc_bytes = acc.into_bigint().to_bytes_le();
+    acc_bytes.truncate(16);
+    let acc = u128::from_le_bytes(acc_bytes.try_into().unwrap());
+    let s = u128::from_le_bytes(key[16..].try_into().unwrap());
+    acc.wrapping_add(s).to_le_bytes().to_vec()
+}
+
+/// The Poly1305 input of the ChaCha20-Poly1305 AEAD (RFC 8439, 2.8).
+pub fn aead_mac_data(aad: &[u8], cipher_texts: &[u8]) -> Vec<u8> {
+    let mut data = aad.to_vec();
+    data.resize(aad.len().div_ceil(16) * 16, 0);
+    data.extend_from_slice(cipher_texts);
+    data.resize(data.len().div_ceil(16) * 16, 0);
+    data.extend((aad.len() as u64).to_le_bytes());
+    data.extend((cipher_texts.len() as u64).to_le_bytes());
+    data
+}
+
+/// The ChaCha20-Poly1305 tag of `cipher_texts`, keyed by key stream block 0.
+pub fn aead_tag(key: &[u8], nonce: &[u8], aad: &[u8], cipher_texts: &[u8]) -> Vec<u8> {
+    let one_time_key = block(key, nonce, 0);
+    mac(&one_time_key[..32], &aead_mac_data(aad, cipher_texts))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{aead_mac_data, aead_tag, mac};
+    use crate::chacha20::native::apply_keystream;
+
+    #[test]
+    fn test_mac() {
+        // RFC 8439, 2.5.2
+        let key = hex::decode("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b")
+            .unwrap();
+        let msg = b"Cryptographic Forum Research Group";
+        assert_eq!(
+            hex::encode(mac(&key, msg)),
+            "a8061dc1305136c6c22b8baf0c0127a9"
+        );
+    }
+
+    #[test]
+    fn test_aead_mac_data() {
+        let data = aead_mac_data(&[1, 2, 3], &[4; 17]);
+        assert_eq!(data.len(), 16 + 32 + 16);
+        assert_eq!(&data[..4], &[1, 2, 3, 0]);
+        assert_eq!(data[48], 3);
+        assert_eq!(data[56], 17);
+    }
+
+    #[test]
+    fn test_aead_tag() {
+        // RFC 8439, 2.8.2
+        let key = hex::decode("808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f")
+            .unwrap();
+        let nonce = hex::decode("070000004041424344454647").unwrap();
+        let aad = hex::decode("50515253c0c1c2c3c4c5c6c7").unwrap();
+        let plain_texts = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+
+        let cipher_texts = apply_keystream(&key, &nonce, 1, plain_texts);
+        assert_eq!(
+            hex::encode(aead_tag(&key, &nonce, &aad, &cipher_texts)),
+            "1ae10b594f09e26a7e902ecbd0600691"
+        );
+    }
+}
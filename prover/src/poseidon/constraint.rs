@@ -0,0 +1,56 @@
+use ark_bn254::Fr;
+use ark_crypto_primitives::sponge::{
+    constraints::CryptographicSpongeVar, poseidon::constraints::PoseidonSpongeVar,
+};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+
+use super::PoseidonBn254;
+
+#[derive(Clone)]
+pub struct PoseidonBn254Var {
+    pub cs: ConstraintSystemRef<Fr>,
+}
+
+impl PoseidonBn254Var {
+    pub fn new(cs: ConstraintSystemRef<Fr>) -> Self {
+        Self { cs }
+    }
+
+    /// The gadget counterpart of `PoseidonBn254::hash`.
+    pub fn generate_constraints(&self, state: &[FpVar<Fr>]) -> Result<FpVar<Fr>, SynthesisError> {
+        let mut sponge = PoseidonSpongeVar::new(self.cs.clone(), PoseidonBn254::config());
+        sponge.absorb(&state)?;
+        Ok(sponge.squeeze_field_elements(1)?[0].clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Fr;
+    use ark_ff::UniformRand;
+    use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar, R1CSVar};
+    use ark_relations::{ns, r1cs::ConstraintSystem};
+    use ark_std::test_rng;
+
+    use super::PoseidonBn254Var;
+    use crate::poseidon::PoseidonBn254;
+
+    #[test]
+    fn test_poseidon() {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let mut rand = test_rng();
+
+        let inputs = (0..5).map(|_| Fr::rand(&mut rand)).collect::<Vec<_>>();
+        let input_vars = inputs
+            .iter()
+            .map(|x| FpVar::new_witness(ns!(cs, "alloc input"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+
+        let output_var = PoseidonBn254Var::new(cs.clone())
+            .generate_constraints(&input_vars)
+            .unwrap();
+        assert_eq!(output_var.value().unwrap(), PoseidonBn254::hash(&inputs));
+        assert!(cs.is_satisfied().unwrap());
+    }
+}
@@ -0,0 +1,70 @@
+use std::sync::OnceLock;
+
+use ark_bn254::Fr;
+use ark_crypto_primitives::sponge::{
+    poseidon::{find_poseidon_ark_and_mds, PoseidonConfig, PoseidonSponge},
+    CryptographicSponge,
+};
+use ark_ff::PrimeField;
+
+pub mod constraint;
+
+/// Poseidon over BN254 with width 3 (rate 2), x^5 S-boxes, 8 full and 57
+/// partial rounds, constants from the reference Grain LFSR.
+#[derive(Debug, Clone)]
+pub struct PoseidonBn254;
+
+impl PoseidonBn254 {
+    pub const RATE: usize = 2;
+    pub const FULL_ROUNDS: usize = 8;
+    pub const PARTIAL_ROUNDS: usize = 57;
+    pub const ALPHA: u64 = 5;
+
+    pub fn config() -> &'static PoseidonConfig<Fr> {
+        static CONFIG: OnceLock<PoseidonConfig<Fr>> = OnceLock::new();
+        CONFIG.get_or_init(|| {
+            let (ark, mds) = find_poseidon_ark_and_mds::<Fr>(
+                Fr::MODULUS_BIT_SIZE as u64,
+                Self::RATE,
+                Self::FULL_ROUNDS as u64,
+                Self::PARTIAL_ROUNDS as u64,
+                0,
+            );
+            PoseidonConfig::new(
+                Self::FULL_ROUNDS,
+                Self::PARTIAL_ROUNDS,
+                Self::ALPHA,
+                mds,
+                ark,
+                Self::RATE,
+                1,
+            )
+        })
+    }
+
+    /// Absorb `state` and squeeze one element.
+    pub fn hash(state: &[Fr]) -> Fr {
+        let mut sponge = PoseidonSponge::new(Self::config());
+        sponge.absorb(&state);
+        sponge.squeeze_field_elements(1)[0]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Fr;
+
+    use super::PoseidonBn254;
+
+    #[test]
+    fn test_poseidon() {
+        let a = PoseidonBn254::hash(&[Fr::from(1u8), Fr::from(2u8)]);
+        assert_eq!(a, PoseidonBn254::hash(&[Fr::from(1u8), Fr::from(2u8)]));
+        assert_ne!(a, PoseidonBn254::hash(&[Fr::from(2u8), Fr::from(1u8)]));
+        // pinned, as every commitment changes with the parameters
+        assert_eq!(
+            a.to_string(),
+            "7142104613055408817911962100316808866448378443474503659992478482890339429929"
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey};
+use ark_relations::r1cs::SynthesisError;
+use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_std::rand::{CryptoRng, RngCore};
+
+use crate::{
+    build_cs::ZkPrompt, commitment::CommitmentGadget, suite::CipherSuite, template::RequestTemplate,
+};
+
+/// The public inputs of `ZkPrompt`, in allocation order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicInputs {
+    pub seq: u64,
+    pub prompt_len_min: u64,
+    pub prompt_len_max: u64,
+    pub prompt_commitment: Fr,
+    pub cipher_commitment: Fr,
+    pub secret_commitment: Fr,
+    /// Present iff the template sets `bind_api_key`.
+    pub api_key_commitment: Option<Fr>,
+    /// One per `num_messages` of the template, see `commit_message`.
+    pub message_commitments: Vec<Fr>,
+    /// Present iff the template sets `tools`, see `commit_tools`.
+    pub tools_commitment: Option<Fr>,
+    /// The values of `disclose` in the template, see `slice::pack_slice`.
+    pub disclosed: Vec<Vec<Fr>>,
+    /// The values of `numbers` in the template, in fixed point.
+    pub numbers: Vec<u64>,
+}
+
+impl PublicInputs {
+    pub fn to_vec(&self) -> Vec<Fr> {
+        vec![
+            Fr::from(self.seq),
+            Fr::from(self.prompt_len_min),
+            Fr::from(self.prompt_len_max),
+            self.prompt_commitment,
+            self.cipher_commitment,
+            self.secret_commitment,
+        ]
+        .into_iter()
+        .chain(self.api_key_commitment)
+        .chain(self.message_commitments.iter().copied())
+        .chain(self.tools_commitment)
+        .chain(self.disclosed.iter().flatten().copied())
+        .chain(self.numbers.iter().map(|n| Fr::from(*n)))
+        .collect()
+    }
+}
+
+/// Generate the proving and verifying keys for requests following
+/// `template` in records of `record_lens` bytes, encrypted under `suite`.
+pub fn setup<C: CommitmentGadget, R: RngCore + CryptoRng>(
+    template: &RequestTemplate,
+    suite: CipherSuite,
+    record_lens: &[usize],
+    rng: &mut R,
+) -> Result<(ProvingKey<Bn254>, VerifyingKey<Bn254>), SynthesisError> {
+    Groth16::<Bn254>::setup(ZkPrompt::<C>::empty(template, suite, record_lens), rng)
+}
+
+pub fn prove<C: CommitmentGadget, R: RngCore + CryptoRng>(
+    pk: &ProvingKey<Bn254>,
+    circuit: ZkPrompt<C>,
+    rng: &mut R,
+) -> Result<(Proof<Bn254>, PublicInputs), SynthesisError> {
+    let public_inputs = circuit.public_inputs()?;
+    let proof = Groth16::<Bn254>::prove(pk, circuit, rng)?;
+    Ok((proof, public_inputs))
+}
+
+pub fn verify(
+    vk: &VerifyingKey<Bn254>,
+    public_inputs: &PublicInputs,
+    proof: &Proof<Bn254>,
+) -> Result<bool, SynthesisError> {
+    Groth16::<Bn254>::verify(vk, &public_inputs.to_vec(), proof)
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Fr;
+    use ark_ff::Field;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+    use super::{prove, setup, verify};
+    use crate::{
+        build_cs::test::sample_prompt, mimc::bn254::MimcBn254, slice::pack_slice,
+        suite::CipherSuite,
+    };
+
+    #[test]
+    fn test_prove_and_verify() {
+        let circuit = sample_prompt(CipherSuite::Chacha20Poly1305Sha256);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let (pk, vk) = setup::<MimcBn254, _>(
+            &circuit.template,
+            circuit.suite,
+            &circuit
+                .records
+                .iter()
+                .map(|record| record.cipher_texts.len())
+                .collect::<Vec<_>>(),
+            &mut rng,
+        )
+        .unwrap();
+
+        let (proof, public_inputs) = prove(&pk, circuit, &mut rng).unwrap();
+        assert!(verify(&vk, &public_inputs, &proof).unwrap());
+
+        let mut wrong_inputs = public_inputs.clone();
+        wrong_inputs.prompt_commitment += Fr::ONE;
+        assert!(!verify(&vk, &wrong_inputs, &proof).unwrap());
+
+        let mut wrong_inputs = public_inputs.clone();
+        wrong_inputs.seq += 1;
+        assert!(!verify(&vk, &wrong_inputs, &proof).unwrap());
+
+        let mut wrong_inputs = public_inputs.clone();
+        wrong_inputs.prompt_len_max += 1;
+        assert!(!verify(&vk, &wrong_inputs, &proof).unwrap());
+
+        // a temperature of zero, although 0.7 was sent
+        let mut wrong_inputs = public_inputs.clone();
+        wrong_inputs.numbers[0] = 0;
+        assert!(!verify(&vk, &wrong_inputs, &proof).unwrap());
+
+        let mut wrong_inputs = public_inputs;
+        wrong_inputs.disclosed[0] = pack_slice(b"\"gpt-4o\"", 32);
+        assert!(!verify(&vk, &wrong_inputs, &proof).unwrap());
+    }
+}
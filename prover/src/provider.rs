@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{anthropic, gemini, json::JsonPath, openai, template::RequestTemplate};
+
+/// The API a request template targets, which fixes the layout of its
+/// requests. Self-hosted servers such as vLLM and llama.cpp speak `openai`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    #[default]
+    OpenAI,
+    Anthropic,
+    Gemini,
+}
+
+impl Provider {
+    pub fn layout(&self) -> Layout {
+        match self {
+            Provider::OpenAI => openai::layout(),
+            Provider::Anthropic => anthropic::layout(),
+            Provider::Gemini => gemini::layout(),
+        }
+    }
+}
+
+/// Where the requests of a provider keep the API key, the system prompt
+/// and the messages.
+#[derive(Clone)]
+pub struct Layout {
+    /// The lowercase name of the header holding the API key, and the bytes
+    /// of its value before the key.
+    pub api_key_header: &'static str,
+    pub api_key_prefix: &'static [u8],
+    /// Headers pinned on top of the host, the content type and the content
+    /// length, by lowercase name.
+    pub pinned_headers: Vec<(&'static str, Vec<u8>)>,
+    /// The path of the system prompt, and of a role it must have if any.
+    pub prompt: JsonPath,
+    pub prompt_role: Option<(JsonPath, &'static [u8])>,
+    /// The paths of the role and of the text of message `i`.
+    pub message_paths: fn(usize) -> [JsonPath; 2],
+    /// Values the server reads along with the prompt and with the text of
+    /// message `i`, such as further parts, which must be absent.
+    pub prompt_excluded: Vec<JsonPath>,
+    pub message_excluded: fn(usize) -> Vec<JsonPath>,
+    /// The paths of the tool definitions, committed when the template asks.
+    pub tool_paths: Vec<JsonPath>,
+    /// Nesting depth of the body, enough for every path above.
+    pub max_json_depth: usize,
+}
+
+/// The request templates of known hosts.
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+    templates: Vec<RequestTemplate>,
+}
+
+impl Registry {
+    /// The templates of `templates/`.
+    pub fn builtin() -> Self {
+        let mut registry = Self::default();
+        for toml in [
+            include_str!("../templates/openai.toml"),
+            include_str!("../templates/anthropic.toml"),
+            include_str!("../templates/gemini.toml"),
+            include_str!("../templates/vllm.toml"),
+        ] {
+            registry.register(RequestTemplate::from_toml(toml).expect("invalid template"));
+        }
+        registry
+    }
+
+    /// Add `template`, replacing the one of the same host.
+    pub fn register(&mut self, template: RequestTemplate) {
+        self.templates
+            .retain(|t| !t.host.eq_ignore_ascii_case(&template.host));
+        self.templates.push(template);
+    }
+
+    /// The template of the requests to `host`, as in their `Host` header.
+    pub fn template(&self, host: &str) -> Option<&RequestTemplate> {
+        self.templates
+            .iter()
+            .find(|t| t.host.eq_ignore_ascii_case(host))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Provider, Registry};
+
+    #[test]
+    fn test_registry() {
+        let mut registry = Registry::builtin();
+        let template = registry.template("API.anthropic.com").unwrap();
+        assert_eq!(template.provider, Provider::Anthropic);
+        assert_eq!(template.url, "/v1/messages");
+        assert_eq!(
+            registry.template("api.openai.com").unwrap().provider,
+            Provider::OpenAI
+        );
+        assert_eq!(
+            registry.template("localhost:8000").unwrap().provider,
+            Provider::OpenAI
+        );
+        assert!(registry.template("localhost:8080").is_none());
+
+        let mut llama = registry.template("localhost:8000").unwrap().clone();
+        llama.host = "localhost:8080".to_string();
+        registry.register(llama);
+        assert!(registry.template("localhost:8080").is_some());
+    }
+}
@@ -0,0 +1,292 @@
+use ark_bn254::Fr;
+use ark_r1cs_std::{
+    alloc::AllocVar, convert::ToBitsGadget, fields::fp::FpVar, uint32::UInt32, uint8::UInt8,
+};
+use ark_relations::{
+    ns,
+    r1cs::{ConstraintSystemRef, SynthesisError},
+};
+
+use crate::{
+    aes::AesGcmVar,
+    chacha20::{native::CONSTANTS, ChaCha20Var},
+    hkdf::{
+        native::{derive_traffic_iv, derive_traffic_key},
+        HkdfVar,
+    },
+    poly1305::{aead_mac_data, Poly1305Var},
+    suite::CipherSuite,
+    utils::{enforce_equals, tls13_aad, tls13_nonce, tls13_nonce_var},
+};
+
+/// The largest plain texts of a TLS 1.3 record.
+pub const MAX_RECORD_LEN: usize = 1 << 14;
+
+/// The content type of application data, which ends the content of a
+/// `TLSInnerPlaintext` before its zero padding.
+pub const APPLICATION_DATA: u8 = 0x17;
+
+/// A TLS 1.3 application data record as sent on the wire.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub cipher_texts: Vec<u8>,
+    pub tag: Vec<u8>,
+}
+
+impl Record {
+    /// The cipher texts followed by the tag.
+    pub fn wire(&self) -> Vec<u8> {
+        [self.cipher_texts.as_slice(), &self.tag].concat()
+    }
+}
+
+/// Decrypt record `seq` protected by the traffic `secret`, natively.
+pub fn decrypt(suite: CipherSuite, secret: &[u8], seq: u64, cipher_texts: &[u8]) -> Vec<u8> {
+    let key = derive_traffic_key(secret, suite.key_len());
+    let nonce = tls13_nonce(&derive_traffic_iv(secret), seq);
+    suite.apply_keystream(&key, &nonce, cipher_texts)
+}
+
+/// Encrypt `plain_texts` as record `seq`, returning the cipher texts and the
+/// tag.
+pub fn encrypt(
+    suite: CipherSuite,
+    secret: &[u8],
+    seq: u64,
+    plain_texts: &[u8],
+) -> (Vec<u8>, Vec<u8>) {
+    let key = derive_traffic_key(secret, suite.key_len());
+    let nonce = tls13_nonce(&derive_traffic_iv(secret), seq);
+    let cipher_texts = suite.apply_keystream(&key, &nonce, plain_texts);
+    let tag = suite.tag(
+        &key,
+        &nonce,
+        &tls13_aad(cipher_texts.len() + 16),
+        &cipher_texts,
+    );
+    (cipher_texts, tag)
+}
+
+/// The `TLSInnerPlaintext` of application data `content`, followed by
+/// `padding_len` zeros.
+pub fn inner_plaintext(content: &[u8], padding_len: usize) -> Vec<u8> {
+    let mut inner = content.to_vec();
+    inner.push(APPLICATION_DATA);
+    inner.resize(inner.len() + padding_len, 0);
+    inner
+}
+
+/// The content and the content type of a `TLSInnerPlaintext`, the type
+/// being its last non-zero byte.
+pub fn split_inner_plaintext(inner: &[u8]) -> (&[u8], u8) {
+    match inner.iter().rposition(|b| *b != 0) {
+        Some(i) => (&inner[..i], inner[i]),
+        None => (&[], 0),
+    }
+}
+
+/// Encrypt `plain_texts` as application data records `seq, seq + 1, ..` of
+/// at most `max_len` bytes of inner plain texts each, as a large write is
+/// split on the wire.
+pub fn encrypt_records(
+    suite: CipherSuite,
+    secret: &[u8],
+    seq: u64,
+    plain_texts: &[u8],
+    max_len: usize,
+    padding_len: usize,
+) -> Vec<Record> {
+    plain_texts
+        .chunks(max_len - 1 - padding_len)
+        .zip(seq..)
+        .map(|(chunk, seq)| {
+            let inner = inner_plaintext(chunk, padding_len);
+            let (cipher_texts, tag) = encrypt(suite, secret, seq, &inner);
+            Record { cipher_texts, tag }
+        })
+        .collect()
+}
+
+/// Decrypt records `seq, seq + 1, ..` and concatenate their contents.
+pub fn decrypt_records(suite: CipherSuite, secret: &[u8], seq: u64, records: &[Record]) -> Vec<u8> {
+    records
+        .iter()
+        .zip(seq..)
+        .flat_map(|(record, seq)| {
+            let inner = decrypt(suite, secret, seq, &record.cipher_texts);
+            split_inner_plaintext(&inner).0.to_vec()
+        })
+        .collect()
+}
+
+/// One TLS 1.3 application data record, authenticated and decrypted under
+/// keys derived from a witnessed traffic secret.
+pub struct RecordVar {
+    pub secret_vars: Vec<UInt8<Fr>>,
+    pub cipher_vars: Vec<UInt8<Fr>>,
+    pub tag_vars: Vec<UInt8<Fr>>,
+    pub plain_vars: Vec<UInt8<Fr>>,
+}
+
+impl RecordVar {
+    pub fn new(
+        cs: ConstraintSystemRef<Fr>,
+        suite: CipherSuite,
+        secret_vars: &[UInt8<Fr>],
+        seq_var: &FpVar<Fr>,
+        cipher_texts: &[u8],
+        tag: &[u8],
+    ) -> Result<Self, SynthesisError> {
+        let record = Record {
+            cipher_texts: cipher_texts.to_vec(),
+            tag: tag.to_vec(),
+        };
+        Ok(Self::sequence(cs, suite, secret_vars, seq_var, &[record])?.remove(0))
+    }
+
+    /// Records `seq, seq + 1, ..` under the same traffic secret, whose key
+    /// and iv are derived once.
+    pub fn sequence(
+        cs: ConstraintSystemRef<Fr>,
+        suite: CipherSuite,
+        secret_vars: &[UInt8<Fr>],
+        seq_var: &FpVar<Fr>,
+        records: &[Record],
+    ) -> Result<Vec<Self>, SynthesisError> {
+        let hkdf = HkdfVar::new(secret_vars)?;
+        let key_vars = hkdf.traffic_key(suite.key_len())?;
+        let iv_vars = hkdf.traffic_iv()?;
+
+        let mut record_vars = vec![];
+        for (i, record) in records.iter().enumerate() {
+            let nonce_vars = tls13_nonce_var(&iv_vars, &(seq_var + Fr::from(i as u64)))?;
+            record_vars.push(Self::decrypt(
+                cs.clone(),
+                suite,
+                secret_vars,
+                &key_vars,
+                &nonce_vars,
+                record,
+            )?);
+        }
+        Ok(record_vars)
+    }
+
+    fn decrypt(
+        cs: ConstraintSystemRef<Fr>,
+        suite: CipherSuite,
+        secret_vars: &[UInt8<Fr>],
+        key_vars: &[UInt8<Fr>],
+        nonce_vars: &[UInt8<Fr>],
+        record: &Record,
+    ) -> Result<Self, SynthesisError> {
+        let (cipher_texts, tag) = (&record.cipher_texts, &record.tag);
+        let cipher_vars = cipher_texts
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc cipher"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+        let tag_vars = tag
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc tag"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+        let aad_vars = tls13_aad(cipher_vars.len() + 16)
+            .into_iter()
+            .map(UInt8::constant)
+            .collect::<Vec<_>>();
+
+        let (plain_vars, expect_tag_vars) = match suite {
+            CipherSuite::Chacha20Poly1305Sha256 => {
+                chacha20_poly1305(key_vars, nonce_vars, &aad_vars, &cipher_vars)?
+            }
+            CipherSuite::Aes128GcmSha256 => {
+                aes_128_gcm(key_vars, nonce_vars, &aad_vars, &cipher_vars)?
+            }
+        };
+        enforce_equals(&expect_tag_vars, &tag_vars)?;
+
+        Ok(Self {
+            secret_vars: secret_vars.to_vec(),
+            cipher_vars,
+            tag_vars,
+            plain_vars,
+        })
+    }
+
+    /// The content of the `TLSInnerPlaintext` of the record, enforcing that
+    /// its type is application data and that its `padding_len` bytes of
+    /// padding are zero, so that no other record passes for a request.
+    pub fn content_vars(&self, padding_len: usize) -> Result<Vec<UInt8<Fr>>, SynthesisError> {
+        let n = self.plain_vars.len();
+        assert!(n > padding_len);
+
+        let mut tail = vec![APPLICATION_DATA];
+        tail.resize(1 + padding_len, 0);
+        let tail_vars = tail.into_iter().map(UInt8::constant).collect::<Vec<_>>();
+        enforce_equals(&tail_vars, &self.plain_vars[n - 1 - padding_len..])?;
+
+        Ok(self.plain_vars[..n - 1 - padding_len].to_vec())
+    }
+
+    /// The record as sent on the wire, cipher texts followed by the tag.
+    pub fn wire_vars(&self) -> Vec<UInt8<Fr>> {
+        [self.cipher_vars.as_slice(), &self.tag_vars].concat()
+    }
+}
+
+/// The plain text and expected tag variables of a record.
+type Decrypted = (Vec<UInt8<Fr>>, Vec<UInt8<Fr>>);
+
+/// Decrypt with ChaCha20 and return the plain texts and the Poly1305 tag.
+fn chacha20_poly1305(
+    key_vars: &[UInt8<Fr>],
+    nonce_vars: &[UInt8<Fr>],
+    aad_vars: &[UInt8<Fr>],
+    cipher_vars: &[UInt8<Fr>],
+) -> Result<Decrypted, SynthesisError> {
+    let key_vars = to_words_le(key_vars)?;
+    let nonce_vars = to_words_le(nonce_vars)?;
+    let qr_constant_vars = CONSTANTS.map(UInt32::constant);
+
+    // the payload starts at block 1, block 0 keys Poly1305
+    let count_var = UInt32::constant(1);
+    let mut chacha20 = ChaCha20Var::new(
+        &qr_constant_vars,
+        &key_vars,
+        &nonce_vars,
+        count_var,
+        cipher_vars,
+    );
+    chacha20.generate_constraints()?;
+
+    let one_time_key = chacha20.key_stream(&UInt32::constant(0))?;
+    let poly1305 = Poly1305Var::new(&one_time_key[..32])?;
+    let tag_vars = poly1305.generate_constraints(&aead_mac_data(aad_vars, cipher_vars))?;
+
+    Ok((chacha20.output_vars, tag_vars))
+}
+
+/// Decrypt with AES-128 in GCTR mode and return the plain texts and the tag.
+fn aes_128_gcm(
+    key_vars: &[UInt8<Fr>],
+    nonce_vars: &[UInt8<Fr>],
+    aad_vars: &[UInt8<Fr>],
+    cipher_vars: &[UInt8<Fr>],
+) -> Result<Decrypted, SynthesisError> {
+    let mut aes_gcm = AesGcmVar::new(key_vars, nonce_vars, cipher_vars)?;
+    aes_gcm.generate_constraints()?;
+    let tag_vars = aes_gcm.tag(aad_vars, cipher_vars)?;
+
+    Ok((aes_gcm.output_vars, tag_vars))
+}
+
+fn to_words_le(vars: &[UInt8<Fr>]) -> Result<Vec<UInt32<Fr>>, SynthesisError> {
+    let mut words = vec![];
+    for chunk in vars.chunks(4) {
+        let mut bits = vec![];
+        for var in chunk {
+            bits.extend(var.to_bits_le()?);
+        }
+        words.push(UInt32::from_bits_le(&bits));
+    }
+    Ok(words)
+}
@@ -0,0 +1,15 @@
+use ark_ff::PrimeField;
+use ark_relations::r1cs::SynthesisError;
+
+use crate::http::HeadVar;
+
+pub trait ReqConstraint<F: PrimeField> {
+    fn req_line(&self) -> Vec<u8>;
+
+    /// The header values pinned by the template, by lowercase name.
+    fn pinned_headers(&self) -> Vec<(&'static str, Vec<u8>)>;
+
+    /// Enforce the request line and the pinned headers, returning the head
+    /// located in the request.
+    fn generate_constraints(&self) -> Result<HeadVar<F>, SynthesisError>;
+}
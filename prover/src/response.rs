@@ -0,0 +1,248 @@
+use std::marker::PhantomData;
+
+use ark_bn254::Fr;
+use ark_ff::Zero;
+use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, fields::fp::FpVar, uint8::UInt8, R1CSVar};
+use ark_relations::{
+    ns,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+
+use crate::{
+    commitment::{
+        commit_cipher, commit_cipher_var, commit_output, commit_output_var, commit_secret,
+        commit_secret_var, CommitmentGadget,
+    },
+    mimc::bn254::MimcBn254,
+    openai::resp::{traits::RespConstraint, RespVar},
+    record::{decrypt_records, Record, RecordVar},
+    suite::CipherSuite,
+    template::ResponseTemplate,
+};
+
+/// The public inputs of `ZkResponse`, in allocation order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResponsePublicInputs {
+    pub seq: u64,
+    pub output_commitment: Fr,
+    pub cipher_commitment: Fr,
+    pub secret_commitment: Fr,
+}
+
+impl ResponsePublicInputs {
+    pub fn to_vec(&self) -> Vec<Fr> {
+        vec![
+            Fr::from(self.seq),
+            self.output_commitment,
+            self.cipher_commitment,
+            self.secret_commitment,
+        ]
+    }
+}
+
+/// Proves that server to client records decrypt to a response whose
+/// `choices[0].message.content` opens the output commitment.
+pub struct ZkResponse<C: CommitmentGadget = MimcBn254> {
+    pub template: ResponseTemplate,
+    pub suite: CipherSuite,
+    /// The application data records carrying the response, in order.
+    pub records: Vec<Record>,
+    /// The server application traffic secret.
+    pub secret: Vec<u8>,
+    /// The sequence number of the first record within the session, public.
+    pub seq: u64,
+    /// Blinding factor of the output commitment.
+    pub output_salt: Fr,
+    /// The hash used for every commitment.
+    pub commitment: PhantomData<C>,
+}
+
+impl<C: CommitmentGadget> ZkResponse<C> {
+    /// A circuit with the shape of records of `record_lens` bytes of cipher
+    /// texts and an all zero witness, used for key generation.
+    pub fn empty(template: &ResponseTemplate, suite: CipherSuite, record_lens: &[usize]) -> Self {
+        Self {
+            template: template.clone(),
+            suite,
+            records: record_lens
+                .iter()
+                .map(|len| Record {
+                    cipher_texts: vec![0; *len],
+                    tag: vec![0; 16],
+                })
+                .collect(),
+            secret: vec![0; 32],
+            seq: 0,
+            output_salt: Fr::zero(),
+            commitment: PhantomData,
+        }
+    }
+
+    /// Compute the public inputs natively, as `generate_constraints` does,
+    /// failing if the response would not satisfy it.
+    pub fn public_inputs(&self) -> Result<ResponsePublicInputs, SynthesisError> {
+        let plain_texts = decrypt_records(self.suite, &self.secret, self.seq, &self.records);
+        let output = RespVar::<Fr>::new(&[], &self.template)
+            .output(&plain_texts)
+            .ok_or(SynthesisError::Unsatisfiable)?;
+        if output.len() > self.template.max_output_len {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let wire = self
+            .records
+            .iter()
+            .flat_map(Record::wire)
+            .collect::<Vec<_>>();
+        Ok(ResponsePublicInputs {
+            seq: self.seq,
+            output_commitment: commit_output::<C>(
+                output,
+                self.template.max_output_len,
+                self.output_salt,
+            ),
+            cipher_commitment: commit_cipher::<C>(&wire),
+            secret_commitment: commit_secret::<C>(&self.secret),
+        })
+    }
+
+    /// The output and the cipher commitments of the records decrypted under
+    /// `secret_vars` from `seq_var`.
+    pub(crate) fn commitment_vars(
+        &self,
+        cs: ConstraintSystemRef<Fr>,
+        hash_var: &C::Var,
+        secret_vars: &[UInt8<Fr>],
+        seq_var: &FpVar<Fr>,
+    ) -> Result<(FpVar<Fr>, FpVar<Fr>), SynthesisError> {
+        let records =
+            RecordVar::sequence(cs.clone(), self.suite, secret_vars, seq_var, &self.records)?;
+        let mut plain_vars = vec![];
+        for record in records.iter() {
+            plain_vars.extend(record.content_vars(self.template.record_padding)?);
+        }
+
+        let resp_var = RespVar::new(&plain_vars, &self.template);
+        let head = resp_var.generate_constraints()?;
+        let output = resp_var.output_var(&resp_var.body_var(&head)?)?;
+
+        let salt_var = FpVar::new_witness(ns!(cs, "alloc output salt"), || Ok(self.output_salt))?;
+        let wire_vars = records
+            .iter()
+            .flat_map(RecordVar::wire_vars)
+            .collect::<Vec<_>>();
+        Ok((
+            commit_output_var::<C>(hash_var, &output, &salt_var)?,
+            commit_cipher_var::<C>(hash_var, &wire_vars)?,
+        ))
+    }
+}
+
+impl<C: CommitmentGadget> ConstraintSynthesizer<Fr> for ZkResponse<C> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let seq_var = FpVar::new_input(ns!(cs, "public seq"), || Ok(Fr::from(self.seq)))?;
+        let secret_vars = self
+            .secret
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc secret"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+
+        let hash_var = C::new_var(cs.clone());
+        let (output_commitment, cipher_commitment) =
+            self.commitment_vars(cs.clone(), &hash_var, &secret_vars, &seq_var)?;
+        let secret_commitment = commit_secret_var::<C>(&hash_var, &secret_vars)?;
+
+        let pi_output_commitment =
+            FpVar::new_input(ns!(cs, "public output"), || output_commitment.value())?;
+        pi_output_commitment.enforce_equal(&output_commitment)?;
+
+        let pi_cipher_commitment =
+            FpVar::new_input(ns!(cs, "public cipher"), || cipher_commitment.value())?;
+        pi_cipher_commitment.enforce_equal(&cipher_commitment)?;
+
+        let pi_secret_commitment =
+            FpVar::new_input(ns!(cs, "public secret"), || secret_commitment.value())?;
+        pi_secret_commitment.enforce_equal(&secret_commitment)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::marker::PhantomData;
+
+    use ark_bn254::Fr;
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+    use ark_std::{test_rng, UniformRand};
+
+    use super::ZkResponse;
+    use crate::{
+        commitment::commit_output, mimc::bn254::MimcBn254, openai::resp::test::RESPONSE,
+        record::encrypt_records, suite::CipherSuite, template::ResponseTemplate,
+    };
+
+    /// The sample response, split over records of at most 200 bytes.
+    pub(crate) fn sample_response(suite: CipherSuite) -> ZkResponse {
+        let template =
+            ResponseTemplate::from_toml(include_str!("../templates/openai_response.toml")).unwrap();
+
+        let plain_texts = hex::decode(RESPONSE).unwrap();
+        let secret =
+            hex::decode("5f2b6c0e8d9a41e3b7c4d1f09a8e7b6c5d4e3f2a1b0c9d8e7f6a5b4c3d2e1f00")
+                .unwrap();
+        let seq = 0;
+        let records = encrypt_records(
+            suite,
+            &secret,
+            seq,
+            &plain_texts,
+            200,
+            template.record_padding,
+        );
+
+        ZkResponse {
+            template,
+            suite,
+            records,
+            secret,
+            seq,
+            output_salt: Fr::rand(&mut test_rng()),
+            commitment: PhantomData,
+        }
+    }
+
+    #[test]
+    fn test_zk_response() {
+        let circuit = sample_response(CipherSuite::Chacha20Poly1305Sha256);
+        assert_eq!(circuit.records.len(), 3);
+        let public_inputs = circuit.public_inputs().unwrap();
+        assert_eq!(
+            public_inputs.output_commitment,
+            commit_output::<MimcBn254>(b"My name is Zypher.", 64, circuit.output_salt)
+        );
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(
+            cs.borrow().unwrap().instance_assignment[1..],
+            public_inputs.to_vec()
+        );
+
+        // records out of order
+        let mut circuit = sample_response(CipherSuite::Chacha20Poly1305Sha256);
+        circuit.records.swap(0, 1);
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+
+        // an output longer than the template allows
+        let mut circuit = sample_response(CipherSuite::Chacha20Poly1305Sha256);
+        circuit.template.max_output_len = 17;
+        assert!(circuit.public_inputs().is_err());
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+}
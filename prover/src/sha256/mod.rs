@@ -0,0 +1,117 @@
+use ark_ff::PrimeField;
+use ark_r1cs_std::{uint32::UInt32, uint8::UInt8};
+use ark_relations::r1cs::SynthesisError;
+use native::{pad, H0, K};
+
+pub mod native;
+
+/// The initial hash value as constants.
+pub fn initial_state<F: PrimeField>() -> Vec<UInt32<F>> {
+    H0.iter().map(|x| UInt32::constant(*x)).collect()
+}
+
+/// The gadget counterpart of `native::compress`.
+pub fn compress<F: PrimeField>(
+    state_vars: &[UInt32<F>],
+    block_vars: &[UInt8<F>],
+) -> Result<Vec<UInt32<F>>, SynthesisError> {
+    assert_eq!(state_vars.len(), 8);
+    assert_eq!(block_vars.len(), 64);
+
+    let mut w = vec![];
+    for chunk in block_vars.chunks(4) {
+        w.push(UInt32::from_bytes_be(chunk)?);
+    }
+    for i in 16..64 {
+        let s0 = &w[i - 15].rotate_right(7) ^ &w[i - 15].rotate_right(18) ^ (&w[i - 15] >> 3u8);
+        let s1 = &w[i - 2].rotate_right(17) ^ &w[i - 2].rotate_right(19) ^ (&w[i - 2] >> 10u8);
+        w.push(UInt32::wrapping_add_many(&[
+            w[i - 16].clone(),
+            s0,
+            w[i - 7].clone(),
+            s1,
+        ])?);
+    }
+
+    let mut v = state_vars.to_vec();
+    for i in 0..64 {
+        let (a, b, c, e, f, g) = (&v[0], &v[1], &v[2], &v[4], &v[5], &v[6]);
+        let s1 = &e.rotate_right(6) ^ &e.rotate_right(11) ^ e.rotate_right(25);
+        // (e & f) ^ (!e & g) == g ^ (e & (f ^ g))
+        let ch = g ^ &(e & &(f ^ g));
+        let s0 = &a.rotate_right(2) ^ &a.rotate_right(13) ^ a.rotate_right(22);
+        // (a & b) ^ (a & c) ^ (b & c) == (a & b) ^ (c & (a ^ b))
+        let maj = &(a & b) ^ &(c & &(a ^ b));
+
+        let t1 = [v[7].clone(), s1, ch, UInt32::constant(K[i]), w[i].clone()];
+        let a = UInt32::wrapping_add_many(&[t1.as_slice(), &[s0, maj]].concat())?;
+        let e = UInt32::wrapping_add_many(&[t1.as_slice(), &[v[3].clone()]].concat())?;
+
+        v.pop();
+        v.insert(0, a);
+        v[4] = e;
+    }
+
+    state_vars
+        .iter()
+        .zip(v.iter())
+        .map(|(s, x)| UInt32::wrapping_add_many(&[s.clone(), x.clone()]))
+        .collect()
+}
+
+/// The gadget counterpart of `native::finalize`. The lengths are fixed at
+/// synthesis time, so the padding is constant.
+pub fn finalize<F: PrimeField>(
+    state_vars: &[UInt32<F>],
+    msg_vars: &[UInt8<F>],
+    prefix_len: usize,
+) -> Result<Vec<UInt8<F>>, SynthesisError> {
+    let mut data_vars = msg_vars.to_vec();
+    // the padding of an empty message after all the bytes so far
+    data_vars.extend(
+        pad(&[], prefix_len + msg_vars.len())
+            .into_iter()
+            .map(UInt8::constant),
+    );
+
+    let mut state_vars = state_vars.to_vec();
+    for block_vars in data_vars.chunks(64) {
+        state_vars = compress(&state_vars, block_vars)?;
+    }
+
+    let mut digest_vars = vec![];
+    for var in state_vars {
+        digest_vars.extend(var.to_bytes_be()?);
+    }
+    Ok(digest_vars)
+}
+
+pub fn hash<F: PrimeField>(msg_vars: &[UInt8<F>]) -> Result<Vec<UInt8<F>>, SynthesisError> {
+    finalize(&initial_state(), msg_vars, 0)
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, uint8::UInt8, R1CSVar};
+    use ark_relations::{ns, r1cs::ConstraintSystem};
+
+    use super::{hash, native};
+
+    #[test]
+    fn test_sha256_constraint() {
+        for len in [3, 55, 56, 64, 100] {
+            let msg = (0..len).map(|i| (i * 7) as u8).collect::<Vec<_>>();
+
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let msg_vars = msg
+                .iter()
+                .map(|x| UInt8::new_witness(ns!(cs, "alloc msg"), || Ok(*x)).unwrap())
+                .collect::<Vec<_>>();
+            let digest_vars = hash(&msg_vars).unwrap();
+
+            assert_eq!(digest_vars.value().unwrap(), native::hash(&msg));
+            assert!(cs.is_satisfied().unwrap());
+        }
+    }
+}
@@ -0,0 +1,357 @@
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    prelude::Boolean,
+    uint8::UInt8,
+};
+use ark_relations::r1cs::SynthesisError;
+
+use crate::utils::{bytes_to_fp_var, enforce_bits, pack_byte_fp_vars, pack_bytes};
+
+/// The public inputs revealing `bytes` as a slice of at most `max_len`
+/// bytes: its length, then its bytes zero-padded and packed.
+pub fn pack_slice<F: PrimeField>(bytes: &[u8], max_len: usize) -> Vec<F> {
+    assert!(bytes.len() <= max_len);
+
+    let mut padded = bytes.to_vec();
+    padded.resize(max_len, 0);
+    let mut packed = vec![F::from(bytes.len() as u64)];
+    packed.extend(pack_bytes::<F>(&padded));
+    packed
+}
+
+/// The start and the length of the set bits of `mask`, enforcing that they
+/// are one run; both are zero if no bit is set.
+pub fn mask_position<F: PrimeField>(
+    mask: &[Boolean<F>],
+) -> Result<(FpVar<F>, FpVar<F>), SynthesisError> {
+    let mut start_var = FpVar::zero();
+    let mut num_runs = FpVar::zero();
+    let mut previous = Boolean::FALSE;
+    for (j, m) in mask.iter().enumerate() {
+        let rise = FpVar::from(m & !&previous);
+        start_var += &rise * F::from(j as u64);
+        num_runs += rise;
+        previous = m.clone();
+    }
+    (&num_runs * (&num_runs - F::one())).enforce_equal(&FpVar::zero())?;
+
+    let len_var = mask
+        .iter()
+        .fold(FpVar::zero(), |sum, m| sum + FpVar::from(m.clone()));
+    Ok((start_var, len_var))
+}
+
+/// `data[start..start + len]` for a private `start` and `len <= max_len`.
+///
+/// The buffer is shifted left by `start - lead_len` with one layer of
+/// selects per bit, so the cost is `n log n` for an `n` bytes buffer,
+/// whatever the position. The `lead_len` bytes before the slice and the
+/// `tail_len` bytes after it are kept to check delimiters.
+pub struct SliceVar<F: PrimeField> {
+    pub start_var: FpVar<F>,
+    pub len_var: FpVar<F>,
+    /// The bytes of the slice, followed by zeros up to `max_len`.
+    pub byte_vars: Vec<FpVar<F>>,
+    /// `data[start - lead_len..start + max_len + tail_len]`, zero past the
+    /// end of the buffer.
+    window_vars: Vec<FpVar<F>>,
+    lead_len: usize,
+    /// `end_vars[j]` is one iff `len == j`, for `j` in `0..=max_len`.
+    end_vars: Vec<FpVar<F>>,
+}
+
+impl<F: PrimeField> SliceVar<F> {
+    /// Enforce that `lead_len <= start` and `start + len + tail_len <= n`.
+    pub fn new(
+        data_vars: &[UInt8<F>],
+        start_var: &FpVar<F>,
+        len_var: &FpVar<F>,
+        max_len: usize,
+        lead_len: usize,
+        tail_len: usize,
+    ) -> Result<Self, SynthesisError> {
+        let n = data_vars.len();
+        let num_bits = (usize::BITS - n.leading_zeros()) as usize;
+
+        let offset_var = start_var - F::from(lead_len as u64);
+        let rest_var = FpVar::Constant(F::from((n - tail_len) as u64)) - start_var - len_var;
+        enforce_bits(&rest_var, num_bits)?;
+
+        let window_len = lead_len + max_len + tail_len;
+        let window_vars = shift_left(
+            &bytes_to_fp_var(data_vars)?,
+            &enforce_bits(&offset_var, num_bits)?,
+            window_len,
+        )?;
+
+        let mut end_vars = vec![];
+        for j in 0..=max_len {
+            let is_end = len_var.is_eq(&FpVar::Constant(F::from(j as u64)))?;
+            end_vars.push(FpVar::from(is_end));
+        }
+        // exactly one match also bounds the length by `max_len`
+        end_vars
+            .iter()
+            .fold(FpVar::zero(), |sum, e| sum + e)
+            .enforce_equal(&FpVar::one())?;
+
+        let mut byte_vars = vec![];
+        let mut inside_var = FpVar::one();
+        for (j, end_var) in end_vars[..max_len].iter().enumerate() {
+            inside_var -= end_var;
+            byte_vars.push(&window_vars[lead_len + j] * &inside_var);
+        }
+
+        Ok(Self {
+            start_var: start_var.clone(),
+            len_var: len_var.clone(),
+            byte_vars,
+            window_vars,
+            lead_len,
+            end_vars,
+        })
+    }
+
+    /// Enforce `min <= len <= max` for public bounds of at most the maximum
+    /// length, so that only the bucket of the length is revealed.
+    pub fn enforce_len_within(
+        &self,
+        min_var: &FpVar<F>,
+        max_var: &FpVar<F>,
+    ) -> Result<(), SynthesisError> {
+        let num_bits = (usize::BITS - self.byte_vars.len().leading_zeros()) as usize;
+        enforce_bits(&(&self.len_var - min_var), num_bits)?;
+        enforce_bits(&(max_var - &self.len_var), num_bits)?;
+        Ok(())
+    }
+
+    /// The gadget counterpart of `pack_slice`.
+    pub fn pack(&self) -> Vec<FpVar<F>> {
+        let mut packed_vars = vec![self.len_var.clone()];
+        packed_vars.extend(pack_byte_fp_vars(&self.byte_vars));
+        packed_vars
+    }
+
+    /// Enforce that the slice is exactly `bytes`.
+    pub fn enforce_bytes(&self, bytes: &[u8]) -> Result<(), SynthesisError> {
+        assert!(bytes.len() <= self.byte_vars.len());
+
+        self.len_var
+            .enforce_equal(&FpVar::Constant(F::from(bytes.len() as u64)))?;
+        for (var, b) in self.byte_vars.iter().zip(bytes) {
+            var.enforce_equal(&FpVar::Constant(F::from(*b)))?;
+        }
+        Ok(())
+    }
+
+    /// Enforce that the slice is right after `lead`.
+    pub fn enforce_lead(&self, lead: &[u8]) -> Result<(), SynthesisError> {
+        assert!(lead.len() <= self.lead_len);
+
+        let start = self.lead_len - lead.len();
+        for (var, b) in self.window_vars[start..self.lead_len].iter().zip(lead) {
+            var.enforce_equal(&FpVar::Constant(F::from(*b)))?;
+        }
+        Ok(())
+    }
+
+    /// Enforce that the slice is right before `tail`.
+    pub fn enforce_tail(&self, tail: &[u8]) -> Result<(), SynthesisError> {
+        for (k, b) in tail.iter().enumerate() {
+            let mut byte_var = FpVar::zero();
+            for (j, end_var) in self.end_vars.iter().enumerate() {
+                byte_var += end_var * &self.window_vars[self.lead_len + j + k];
+            }
+            byte_var.enforce_equal(&FpVar::Constant(F::from(*b)))?;
+        }
+        Ok(())
+    }
+
+    /// The slice read as a decimal number, enforcing that it is made of
+    /// digits only.
+    pub fn decimal_var(&self) -> Result<FpVar<F>, SynthesisError> {
+        let mut value_var = FpVar::zero();
+        let mut inside_var = FpVar::one();
+        for (byte_var, end_var) in self.byte_vars.iter().zip(&self.end_vars) {
+            inside_var -= end_var;
+            let digit_var = byte_var - &inside_var * F::from(b'0');
+            enforce_bits(&digit_var, 4)?;
+            enforce_bits(&(&inside_var * F::from(9u8) - &digit_var), 4)?;
+            value_var = value_var * (&inside_var * F::from(9u8) + F::one()) + digit_var;
+        }
+        Ok(value_var)
+    }
+
+    /// The slice read as a non-negative JSON number without exponent, in
+    /// fixed point with `decimals` digits after the point, which it may not
+    /// exceed; see `json::native::parse_number`.
+    pub fn number_var(&self, decimals: usize) -> Result<FpVar<F>, SynthesisError> {
+        self.end_vars[0].enforce_equal(&FpVar::zero())?;
+
+        let mut value_var = FpVar::zero();
+        let mut inside_var = FpVar::one();
+        let mut dots_var = FpVar::zero();
+        let mut frac_len_var = FpVar::zero();
+        for (j, byte_var) in self.byte_vars.iter().enumerate() {
+            inside_var -= &self.end_vars[j];
+            let is_dot = byte_var.is_eq(&FpVar::Constant(F::from(b'.')))?;
+            let dot_var = FpVar::from(is_dot.clone());
+            // one for a digit, zero for the dot and past the end
+            let step_var = &inside_var - &dot_var;
+            let digit_var = byte_var - &step_var * F::from(b'0') - &dot_var * F::from(b'.');
+            enforce_bits(&digit_var, 4)?;
+            enforce_bits(&(&step_var * F::from(9u8) - &digit_var), 4)?;
+
+            // the dot sits between digits
+            match j {
+                0 => is_dot.enforce_equal(&Boolean::FALSE)?,
+                _ => (&dot_var * &self.end_vars[j + 1]).enforce_equal(&FpVar::zero())?,
+            }
+            value_var = value_var * (&step_var * F::from(9u8) + F::one()) + digit_var;
+            frac_len_var += &dots_var * &step_var;
+            dots_var += dot_var;
+        }
+        (&dots_var * (&dots_var - F::one())).enforce_equal(&FpVar::zero())?;
+
+        // scale by the missing fraction digits, which also bounds them
+        let mut scale_var = FpVar::zero();
+        let mut matches_var = FpVar::zero();
+        for k in 0..=decimals {
+            let is_len = FpVar::from(frac_len_var.is_eq(&FpVar::Constant(F::from(k as u64)))?);
+            scale_var += &is_len * F::from(10u64).pow([(decimals - k) as u64]);
+            matches_var += is_len;
+        }
+        matches_var.enforce_equal(&FpVar::one())?;
+        Ok(value_var * scale_var)
+    }
+}
+
+/// The `out_len` elements from the offset given by `offset_bits`, with
+/// zeros past the end of `vars`.
+fn shift_left<F: PrimeField>(
+    vars: &[FpVar<F>],
+    offset_bits: &[Boolean<F>],
+    out_len: usize,
+) -> Result<Vec<FpVar<F>>, SynthesisError> {
+    let mut shifted_vars = vars.to_vec();
+    shifted_vars.resize(vars.len().max(out_len), FpVar::zero());
+    for (k, bit) in offset_bits.iter().enumerate() {
+        let shift = 1 << k;
+        shifted_vars = (0..shifted_vars.len())
+            .map(|i| match shifted_vars.get(i + shift) {
+                Some(next_var) => bit.select(next_var, &shifted_vars[i]),
+                None => bit.select(&FpVar::zero(), &shifted_vars[i]),
+            })
+            .collect::<Result<_, _>>()?;
+    }
+    shifted_vars.truncate(out_len);
+    Ok(shifted_vars)
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar, uint8::UInt8, R1CSVar};
+    use ark_relations::{ns, r1cs::ConstraintSystem};
+
+    use super::SliceVar;
+    use crate::json::native::parse_number;
+
+    fn slice_constraints(data: &[u8], start: usize, len: usize, lead: &[u8], tail: &[u8]) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let data_vars = data
+            .iter()
+            .map(|x| UInt8::new_witness(ns!(cs, "alloc data"), || Ok(*x)).unwrap())
+            .collect::<Vec<_>>();
+        let start_var =
+            FpVar::new_witness(ns!(cs, "alloc start"), || Ok(Fr::from(start as u64))).unwrap();
+        let len_var =
+            FpVar::new_witness(ns!(cs, "alloc len"), || Ok(Fr::from(len as u64))).unwrap();
+
+        let slice = SliceVar::new(&data_vars, &start_var, &len_var, 16, 4, 2).unwrap();
+        slice.enforce_lead(lead).unwrap();
+        slice.enforce_tail(tail).unwrap();
+
+        let mut expect = data[start..start + len].to_vec();
+        expect.resize(16, 0);
+        let bytes = slice.byte_vars.value().unwrap();
+        assert_eq!(bytes, expect.into_iter().map(Fr::from).collect::<Vec<_>>());
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_decimal() {
+        for (data, value) in [
+            (&b"length:1024\r\n"[..], Some(1024)),
+            (b"length:0042\r\n", Some(42)),
+            (b"length:1o24\r\n", None),
+        ] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let data_vars = data
+                .iter()
+                .map(|x| UInt8::new_witness(ns!(cs, "alloc data"), || Ok(*x)).unwrap())
+                .collect::<Vec<_>>();
+            let start_var =
+                FpVar::new_witness(ns!(cs, "alloc start"), || Ok(Fr::from(7u8))).unwrap();
+            let len_var = FpVar::new_witness(ns!(cs, "alloc len"), || Ok(Fr::from(4u8))).unwrap();
+
+            let slice = SliceVar::new(&data_vars, &start_var, &len_var, 8, 0, 2).unwrap();
+            let value_var = slice.decimal_var().unwrap();
+            assert_eq!(cs.is_satisfied().unwrap(), value.is_some());
+            if let Some(value) = value {
+                assert_eq!(value_var.value().unwrap(), Fr::from(value as u64));
+            }
+        }
+    }
+
+    #[test]
+    fn test_number() {
+        for value in [
+            &b"0"[..],
+            b"0.7",
+            b"0.05",
+            b"1",
+            b"256",
+            b"0.123",
+            b".5",
+            b"5.",
+            b"1.2.3",
+            b"-1",
+            b"1e3",
+            b"",
+        ] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let data = [b":", value, b","].concat();
+            let data_vars = data
+                .iter()
+                .map(|x| UInt8::new_witness(ns!(cs, "alloc data"), || Ok(*x)).unwrap())
+                .collect::<Vec<_>>();
+            let start_var =
+                FpVar::new_witness(ns!(cs, "alloc start"), || Ok(Fr::from(1u8))).unwrap();
+            let len_var =
+                FpVar::new_witness(ns!(cs, "alloc len"), || Ok(Fr::from(value.len() as u64)))
+                    .unwrap();
+
+            let slice = SliceVar::new(&data_vars, &start_var, &len_var, 8, 1, 1).unwrap();
+            let number_var = slice.number_var(2).unwrap();
+            let expected = parse_number(value, 2);
+            assert_eq!(cs.is_satisfied().unwrap(), expected.is_some(), "{value:?}");
+            if let Some(expected) = expected {
+                assert_eq!(number_var.value().unwrap(), Fr::from(expected));
+            }
+        }
+    }
+
+    #[test]
+    fn test_slice() {
+        let data = b"{\"a\":\"hello\",\"bb\":\"zypher\"}";
+        assert!(slice_constraints(data, 6, 5, b"\":\"", b"\","));
+        assert!(slice_constraints(data, 19, 6, b"\":\"", b"\"}"));
+        // wrong delimiters, or a slice running past the buffer
+        assert!(!slice_constraints(data, 6, 4, b"\":\"", b"\","));
+        assert!(!slice_constraints(data, 19, 6, b"\":\"", b"\","));
+        assert!(!slice_constraints(data, 20, 6, b"\":\"", b"\"}"));
+    }
+}
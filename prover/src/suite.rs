@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{aes, chacha20::native::apply_keystream, poly1305};
+
+/// The TLS 1.3 cipher suites a request record can be proven for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CipherSuite {
+    Chacha20Poly1305Sha256,
+    Aes128GcmSha256,
+}
+
+impl CipherSuite {
+    pub fn key_len(&self) -> usize {
+        match self {
+            CipherSuite::Chacha20Poly1305Sha256 => 32,
+            CipherSuite::Aes128GcmSha256 => 16,
+        }
+    }
+
+    /// Encrypt or decrypt a record payload natively.
+    pub fn apply_keystream(&self, key: &[u8], nonce: &[u8], input: &[u8]) -> Vec<u8> {
+        match self {
+            CipherSuite::Chacha20Poly1305Sha256 => apply_keystream(key, nonce, 1, input),
+            CipherSuite::Aes128GcmSha256 => aes::native::gctr(key, nonce, input),
+        }
+    }
+
+    pub fn tag(&self, key: &[u8], nonce: &[u8], aad: &[u8], cipher_texts: &[u8]) -> Vec<u8> {
+        match self {
+            CipherSuite::Chacha20Poly1305Sha256 => {
+                poly1305::native::aead_tag(key, nonce, aad, cipher_texts)
+            }
+            CipherSuite::Aes128GcmSha256 => aes::native::aead_tag(key, nonce, aad, cipher_texts),
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+
+use crate::provider::Provider;
+
+/// The fixed parts of a request, which together with the cipher length
+/// determine the circuit shape.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestTemplate {
+    /// The API of `host`, which fixes the layout of the request.
+    #[serde(default)]
+    pub provider: Provider,
+    pub url: String,
+    pub host: String,
+    /// Length of the API key, which is a private witness, in the header
+    /// the provider reads it from.
+    pub api_key_len: usize,
+    /// Expose a commitment to the API key as a public input.
+    #[serde(default)]
+    pub bind_api_key: bool,
+    /// Zero bytes padding the `TLSInnerPlaintext` of each record, none
+    /// unless the client pads.
+    #[serde(default)]
+    pub record_padding: usize,
+    /// Upper bound of the system prompt length, whose actual length and
+    /// position in the body are private.
+    pub max_prompt_len: usize,
+    /// Number of `messages` committed one by one together with their role,
+    /// which must be exactly those of the body unless zero.
+    #[serde(default)]
+    pub num_messages: usize,
+    /// Upper bound of the content length of those messages.
+    #[serde(default)]
+    pub max_message_len: usize,
+    /// Body values revealed as public inputs, in order. The request line and
+    /// the host are pinned by the template, so they are public already.
+    #[serde(default)]
+    pub disclose: Vec<Disclosure>,
+    /// Commit to the tool definitions of the layout, such as `tools` and
+    /// `tool_choice`, as `tools_commitment`.
+    #[serde(default)]
+    pub tools: Option<ToolsCommitment>,
+    /// Numeric body values revealed as public inputs, in order, such as
+    /// `temperature` or `seed`; the body must have them.
+    #[serde(default)]
+    pub numbers: Vec<NumberDisclosure>,
+}
+
+/// A number of the request body revealed as a public input in fixed point,
+/// see `json::native::parse_number`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NumberDisclosure {
+    pub path: String,
+    pub max_len: usize,
+    /// Digits kept after the decimal point, so that `0.7` is revealed as
+    /// 70 with two of them.
+    #[serde(default)]
+    pub decimals: usize,
+}
+
+/// The shape of the tool definitions committed together, each raw as in the
+/// body and empty if absent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolsCommitment {
+    /// Upper bound of the length of each definition.
+    pub max_len: usize,
+    /// Nesting depth of the body, raised to fit the parameter schemas.
+    pub max_json_depth: usize,
+}
+
+/// A value of the request body revealed as public inputs, raw as in the
+/// body, i.e. with the quotes of a string.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Disclosure {
+    /// A JSON path such as `model` or `messages[1].role`.
+    pub path: String,
+    pub max_len: usize,
+}
+
+impl RequestTemplate {
+    pub fn from_toml(s: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(s)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+}
+
+/// The fixed parts of a response, whose `choices[0].message.content` is
+/// located in the body.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResponseTemplate {
+    pub status_line: String,
+    /// Zero bytes padding the `TLSInnerPlaintext` of each record, none
+    /// unless the server pads.
+    #[serde(default)]
+    pub record_padding: usize,
+    /// Upper bound of the output length, whose actual length and position
+    /// in the body are private.
+    pub max_output_len: usize,
+}
+
+impl ResponseTemplate {
+    pub fn from_toml(s: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(s)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{RequestTemplate, ResponseTemplate};
+
+    #[test]
+    fn test_load_template() {
+        let from_toml =
+            RequestTemplate::from_toml(include_str!("../templates/openai.toml")).unwrap();
+        let from_json = RequestTemplate::from_json(
+            r#"{"url":"/v1/chat/completions","host":"api.openai.com","api_key_len":10,"bind_api_key":true,"max_prompt_len":64,"num_messages":2,"max_message_len":64,"disclose":[{"path":"model","max_len":32},{"path":"temperature","max_len":8}],"numbers":[{"path":"temperature","max_len":8,"decimals":2}]}"#,
+        )
+        .unwrap();
+        assert_eq!(from_toml, from_json);
+        assert_eq!(from_toml.max_prompt_len, 64);
+        assert_eq!(from_toml.num_messages, 2);
+        assert_eq!(from_toml.disclose[1].path, "temperature");
+    }
+
+    #[test]
+    fn test_load_response_template() {
+        let template =
+            ResponseTemplate::from_toml(include_str!("../templates/openai_response.toml")).unwrap();
+        assert_eq!(template.status_line, "HTTP/1.1 200 OK");
+        assert_eq!(template.record_padding, 0);
+        assert_eq!(template.max_output_len, 64);
+    }
+}
@@ -1,6 +1,12 @@
-use ark_ff::PrimeField;
-use ark_r1cs_std::{eq::EqGadget, fields::fp::FpVar, prelude::Boolean, uint8::UInt8};
-use ark_relations::r1cs::SynthesisError;
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{
+    alloc::AllocVar, convert::ToBitsGadget, eq::EqGadget, fields::fp::FpVar, prelude::Boolean,
+    uint64::UInt64, uint8::UInt8, R1CSVar,
+};
+use ark_relations::{
+    ns,
+    r1cs::{ConstraintSystemRef, SynthesisError},
+};
 
 pub fn enforce_equals<F: PrimeField>(a: &[UInt8<F>], b: &[UInt8<F>]) -> Result<(), SynthesisError> {
     for (v0, v1) in a.iter().zip(b.iter()) {
@@ -9,6 +15,14 @@ pub fn enforce_equals<F: PrimeField>(a: &[UInt8<F>], b: &[UInt8<F>]) -> Result<(
     Ok(())
 }
 
+/// `Boolean::kary_or`, which does not support constants only.
+pub fn any<F: PrimeField>(bits: &[Boolean<F>]) -> Result<Boolean<F>, SynthesisError> {
+    match bits.iter().all(|b| b.is_constant()) {
+        true => Ok(Boolean::constant(bits.iter().any(|b| b.value().unwrap()))),
+        false => Boolean::kary_or(bits),
+    }
+}
+
 pub fn compress_var<F: PrimeField>(
     vars: &[Boolean<F>],
     chunk_len: usize,
@@ -21,21 +35,124 @@ pub fn compress_var<F: PrimeField>(
     Ok(compress_vars)
 }
 
+/// Witness the `num_bits` low bits of `value`, little-endian.
+pub fn alloc_bits<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    value: Result<F, SynthesisError>,
+    num_bits: usize,
+) -> Result<Vec<Boolean<F>>, SynthesisError> {
+    let bits = value.map(|v| v.into_bigint().to_bits_le());
+    (0..num_bits)
+        .map(|i| {
+            Boolean::new_witness(ns!(cs, "alloc bit"), || {
+                bits.as_ref().map(|b| b[i]).map_err(|e| *e)
+            })
+        })
+        .collect()
+}
+
+/// Decompose `var` into `num_bits` little-endian bits, which enforces that
+/// it lies in `[0, 2^num_bits)`.
+pub fn enforce_bits<F: PrimeField>(
+    var: &FpVar<F>,
+    num_bits: usize,
+) -> Result<Vec<Boolean<F>>, SynthesisError> {
+    let bits = alloc_bits(var.cs(), var.value(), num_bits)?;
+    Boolean::le_bits_to_fp(&bits)?.enforce_equal(var)?;
+    Ok(bits)
+}
+
+/// The field element of each byte, at no cost.
+pub fn bytes_to_fp_var<F: PrimeField>(vars: &[UInt8<F>]) -> Result<Vec<FpVar<F>>, SynthesisError> {
+    vars.iter()
+        .map(|var| Boolean::le_bits_to_fp(&var.to_bits_le()?))
+        .collect()
+}
+
+/// Bytes per packed field element, so that every element stays below the
+/// modulus of any field of at least 249 bits.
+pub const PACK_BYTES: usize = 31;
+
+/// The canonical packing of bytes into field elements: each run of 31 bytes,
+/// read as a little-endian integer, is one element, and the last element
+/// holds the remaining bytes.
+pub fn pack_bytes<F: PrimeField>(bytes: &[u8]) -> Vec<F> {
+    bytes
+        .chunks(PACK_BYTES)
+        .map(F::from_le_bytes_mod_order)
+        .collect()
+}
+
+/// The gadget counterpart of `pack_bytes`.
+pub fn pack_bytes_var<F: PrimeField>(vars: &[UInt8<F>]) -> Result<Vec<FpVar<F>>, SynthesisError> {
+    let mut bits = vec![];
+    for var in vars {
+        bits.extend(var.to_bits_le()?);
+    }
+    compress_var(&bits, PACK_BYTES * 8)
+}
+
+/// `pack_bytes` over bytes already held as field elements, which is linear
+/// and so costs no constraints.
+pub fn pack_byte_fp_vars<F: PrimeField>(vars: &[FpVar<F>]) -> Vec<FpVar<F>> {
+    vars.chunks(PACK_BYTES)
+        .map(|chunk| {
+            let mut packed = FpVar::Constant(F::zero());
+            for var in chunk.iter().rev() {
+                packed = packed * F::from(256u16) + var;
+            }
+            packed
+        })
+        .collect()
+}
+
+/// The additional data of a TLS 1.3 application data record, i.e. its header.
+pub fn tls13_aad(payload_len: usize) -> Vec<u8> {
+    let len = (payload_len as u16).to_be_bytes();
+    vec![0x17, 0x03, 0x03, len[0], len[1]]
+}
+
+/// The nonce of record `seq`: the static iv xor-ed with the big-endian
+/// sequence number (RFC 8446, 5.3).
+pub fn tls13_nonce(iv: &[u8], seq: u64) -> Vec<u8> {
+    let mut nonce = iv.to_vec();
+    for (n, s) in nonce[4..].iter_mut().zip(seq.to_be_bytes()) {
+        *n ^= s;
+    }
+    nonce
+}
+
+/// The gadget counterpart of `tls13_nonce`, enforcing that `seq_var` fits in
+/// 64 bits.
+pub fn tls13_nonce_var<F: PrimeField>(
+    iv_vars: &[UInt8<F>],
+    seq_var: &FpVar<F>,
+) -> Result<Vec<UInt8<F>>, SynthesisError> {
+    assert_eq!(iv_vars.len(), 12);
+
+    let (seq_var, _) = UInt64::from_fp(seq_var)?;
+    let mut nonce_vars = iv_vars[..4].to_vec();
+    for (iv, s) in iv_vars[4..].iter().zip(seq_var.to_bytes_be()?) {
+        nonce_vars.push(iv ^ s);
+    }
+    Ok(nonce_vars)
+}
+
 #[cfg(test)]
 mod test {
     use ark_bn254::Fr;
     use ark_ff::PrimeField;
-    use ark_r1cs_std::prelude::Boolean;
     use ark_r1cs_std::R1CSVar;
+    use ark_r1cs_std::{prelude::Boolean, uint8::UInt8};
     use ark_std::test_rng;
     use ark_std::UniformRand;
 
-    use super::compress_var;
+    use super::{compress_var, pack_bytes, pack_bytes_var, tls13_nonce};
 
     fn u8_to_le_bits(n: u8) -> [bool; 8] {
         let mut bits = [false; 8];
-        for i in 0..8 {
-            bits[i] = (n >> i) & 1 == 1;
+        for (i, bit) in bits.iter_mut().enumerate() {
+            *bit = (n >> i) & 1 == 1;
         }
         bits
     }
@@ -46,8 +163,7 @@ mod test {
         let data = (0..1000).map(|_| u8::rand(&mut rng)).collect::<Vec<_>>();
         let data_bytes = data
             .iter()
-            .map(|x| u8_to_le_bits(*x))
-            .flatten()
+            .flat_map(|x| u8_to_le_bits(*x))
             .collect::<Vec<_>>();
 
         let data_var = data_bytes
@@ -62,8 +178,34 @@ mod test {
 
         let expect = data
             .chunks(31)
-            .map(|x| Fr::from_le_bytes_mod_order(x))
+            .map(Fr::from_le_bytes_mod_order)
             .collect::<Vec<_>>();
         assert_eq!(output, expect);
+        assert_eq!(pack_bytes::<Fr>(&data), expect);
+    }
+
+    #[test]
+    fn test_pack_bytes() {
+        let mut rng = test_rng();
+        let data = (0..100).map(|_| u8::rand(&mut rng)).collect::<Vec<_>>();
+        let data_vars = data
+            .iter()
+            .map(|x| UInt8::constant(*x))
+            .collect::<Vec<UInt8<Fr>>>();
+
+        let packed = pack_bytes::<Fr>(&data);
+        assert_eq!(packed.len(), 4);
+        assert_eq!(packed[3], Fr::from_le_bytes_mod_order(&data[93..]));
+        assert_eq!(pack_bytes_var(&data_vars).unwrap().value().unwrap(), packed);
+    }
+
+    #[test]
+    fn test_tls13_nonce() {
+        let iv = hex::decode("4a1f503da88baa6e582a2fe1").unwrap();
+        assert_eq!(tls13_nonce(&iv, 0), iv);
+        assert_eq!(
+            hex::encode(tls13_nonce(&iv, 0x0102)),
+            "4a1f503da88baa6e582a2ee3"
+        );
     }
 }